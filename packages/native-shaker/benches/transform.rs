@@ -0,0 +1,86 @@
+//! Shakes the fixtures of `packages/transform` with `transform_inner`, next to the pipeline it
+//! replaced: the expanded code was printed and parsed again before tree shaking. The second
+//! benchmark adds that round trip to the current pipeline, their difference is the speedup.
+//!
+//! Run with `cargo bench --bench transform`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use native_shaker::{transform_inner, TransformInnerOptions};
+use oxc_allocator::Allocator;
+use oxc_codegen::Codegen;
+use oxc_parser::Parser;
+use oxc_span::SourceType;
+use std::path::Path;
+
+/// Inputs of fixtures as `(fixture name, filename, source text)`, sorted by name.
+fn read_fixtures() -> Vec<(String, String, String)> {
+  let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../transform/__fixtures__");
+  let mut fixtures: Vec<(String, String, String)> = std::fs::read_dir(fixtures_dir)
+    .expect("fixtures of packages/transform should exist")
+    .filter_map(|entry| {
+      let entry = entry.ok()?;
+      let path = entry.path().join("input.ts");
+      let source_text = std::fs::read_to_string(&path).ok()?;
+
+      Some((
+        entry.file_name().to_string_lossy().to_string(),
+        path.to_string_lossy().to_string(),
+        source_text,
+      ))
+    })
+    .collect();
+  fixtures.sort();
+
+  fixtures
+}
+
+fn shake(filename: &String, source_text: &String) -> String {
+  let allocator = Allocator::default();
+
+  transform_inner(
+    &allocator,
+    filename,
+    source_text,
+    vec!["default"],
+    TransformInnerOptions::default(),
+  )
+  .output
+}
+
+fn bench_fixtures(c: &mut Criterion) {
+  let mut group = c.benchmark_group("transform");
+
+  for (name, filename, source_text) in read_fixtures() {
+    let source_type = SourceType::from_path(&filename)
+      .unwrap_or_default()
+      .with_module(true);
+
+    group.bench_with_input(
+      BenchmarkId::new("single_parse", &name),
+      &(&filename, &source_text),
+      |b, (filename, source_text)| b.iter(|| shake(filename, source_text)),
+    );
+
+    group.bench_with_input(
+      BenchmarkId::new("print_and_parse_again", &name),
+      &(&filename, &source_text),
+      |b, (filename, source_text)| {
+        b.iter(|| {
+          let allocator = Allocator::default();
+          let program = Parser::new(&allocator, source_text, source_type)
+            .parse()
+            .program;
+          let code = Codegen::new().build(&program).source_text;
+          black_box(Parser::new(&allocator, &code, source_type).parse());
+
+          shake(filename, source_text)
+        })
+      },
+    );
+  }
+
+  group.finish();
+}
+
+criterion_group!(benches, bench_fixtures);
+criterion_main!(benches);
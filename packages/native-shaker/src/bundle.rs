@@ -0,0 +1,253 @@
+use crate::crawler::{shared_resolver, Crawler, ModuleGraph};
+use crate::module_resolve::ModuleResolver;
+use napi_derive::napi;
+
+/// Name of the function that registers module factories, see `WD_MODULE_NAME` in the transform
+/// package.
+const MODULE_FUNCTION: &str = "__wd40_module";
+
+/// The module system of the bundle, it mirrors `runModule()` of the transform package.
+const RUNTIME: &str = r#"const installedModules = {};
+const registeredModules = {};
+
+function export_star(from, to) {
+  Object.keys(from).forEach(function (k) {
+    if (k !== "default" && !Object.prototype.hasOwnProperty.call(to, k)) {
+      Object.defineProperty(to, k, {
+        enumerable: true,
+        get: function () { return from[k]; }
+      });
+    }
+  });
+  return from;
+}
+
+function export_binding(from, name, to, as) {
+  Object.defineProperty(to, as, {
+    enumerable: true,
+    get: function () { return from[name]; }
+  });
+}
+
+function require(moduleId) {
+  if (installedModules[moduleId]) {
+    return installedModules[moduleId].exports;
+  }
+  if (!registeredModules[moduleId]) {
+    throw new Error("Cannot find module '" + moduleId + "'");
+  }
+
+  const module = { exports: {}, id: moduleId, loaded: false };
+
+  installedModules[moduleId] = module;
+  registeredModules[moduleId].call(null, module, module.exports, require, export_star, export_binding);
+
+  module.loaded = true;
+
+  return module.exports;
+}
+
+function __wd40_module(filename, fn) {
+  registeredModules[filename] = fn;
+}
+"#;
+
+/// A script that evaluates to exports of the entry module, and the graph it was emitted from.
+#[napi(object)]
+pub struct Bundle {
+  /// The script, its completion value is the exports object of the entry module.
+  pub code: String,
+  /// Shaken modules of the bundle, their code is lowered to CommonJS.
+  pub graph: ModuleGraph,
+}
+
+fn js_string(value: &str) -> String {
+  serde_json::to_string(value).expect("a string is always serializable")
+}
+
+/// Wraps modules of a crawled graph to factories keyed by their resolved paths and appends the
+/// module system. Modules are expected to be lowered to CommonJS.
+pub(crate) fn emit_bundle(entry: &str, graph: &ModuleGraph) -> String {
+  let mut code = String::from("(function () {\n\"use strict\";\n\n");
+
+  code.push_str(RUNTIME);
+
+  for module in &graph.modules {
+    code.push_str(&format!(
+      "\n{MODULE_FUNCTION}({}, function (module, exports, require, export_star, export_binding) {{\n{}\n}});\n",
+      js_string(&module.path),
+      module.code.trim_end(),
+    ));
+  }
+
+  code.push_str(&format!(
+    "\nreturn require({});\n}})();\n",
+    js_string(entry)
+  ));
+
+  code
+}
+
+/// Crawls an entry module like {@link crawl} and emits all shaken modules as a single script
+/// that can be evaluated, i.e. with `vm.runInThisContext()`.
+///
+/// @param filename Path of the entry module, imports are resolved relative to it.
+/// @param sourceText Code of the entry module.
+/// @param onlyExports Names of exports that should be kept in the entry module.
+/// @param resolver A resolver from {@link createResolver}, its cache is shared between calls.
+///
+/// @returns the script & the graph of shaken modules.
+#[napi]
+pub fn bundle(
+  filename: String,
+  source_text: String,
+  only_exports: Vec<String>,
+  resolver: Option<&ModuleResolver>,
+) -> Bundle {
+  let crawler = Crawler::new(&shared_resolver(resolver), None, true);
+  let graph = crawler.crawl(filename.clone(), source_text, only_exports);
+
+  Bundle {
+    code: emit_bundle(&filename, &graph),
+    graph,
+  }
+}
+
+#[test]
+fn test_bundle() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "tokens.ts",
+      r#"
+        export * from "./palette";
+        export const unused = "unused";
+      "#,
+    ),
+    ("palette.ts", r#"export const blue = "blue";"#),
+    ("icon.svg", "<svg />"),
+    ("global.css", "body {}"),
+  ]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import "./global.css";
+    import { blue } from "./tokens";
+    import icon from "./icon.svg";
+    export const classes = blue + icon;
+  "#;
+
+  let result = bundle(
+    filename.clone(),
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    None,
+  );
+  let tokens = root.join("tokens.ts").to_string_lossy().to_string();
+  let palette = root.join("palette.ts").to_string_lossy().to_string();
+  let icon = root.join("icon.svg").to_string_lossy().to_string();
+
+  assert_eq!(result.graph.modules.len(), 3);
+  assert!(result.code.starts_with("(function () {\n\"use strict\";"));
+  assert!(result
+    .code
+    .ends_with(&format!("return require({filename:?});\n}})();\n")));
+  for path in [&filename, &tokens, &palette] {
+    assert!(result.code.contains(&format!(
+      "__wd40_module({path:?}, function (module, exports, require, export_star, export_binding) {{"
+    )));
+  }
+  assert!(result
+    .code
+    .contains(&format!("const _tokens = require({tokens:?});")));
+  assert!(result.code.contains("const classes = _tokens.blue + icon;"));
+  assert!(result
+    .code
+    .contains(&format!("const icon = \"@wd40:{icon}:@wd40\";")));
+  assert!(result
+    .code
+    .contains(&format!("export_star(require({palette:?}), exports);")));
+  assert!(result.code.contains("exports.classes = classes;"));
+  assert!(!result.code.contains("global.css"));
+  assert!(!result.code.contains("exports.unused"));
+}
+
+#[test]
+fn test_bundle_cycle() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "a.ts",
+      r#"
+        import { b } from "./b";
+        export function a() {
+          return b;
+        }
+      "#,
+    ),
+    (
+      "b.ts",
+      r#"
+        import { a } from "./a";
+        export const b = a.name;
+      "#,
+    ),
+  ]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import { a } from "./a";
+    export const classes = a();
+  "#;
+
+  let result = bundle(
+    filename,
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    None,
+  );
+  let a = root.join("a.ts").to_string_lossy().to_string();
+  let b = root.join("b.ts").to_string_lossy().to_string();
+
+  assert_eq!(result.graph.modules.len(), 3);
+  // "b.ts" reads "a" while "a.ts" waits for it, the export of the function is already defined
+  let export_a = result.code.find("exports.a = a;").unwrap();
+  let require_b = result
+    .code
+    .find(&format!("const _b = require({b:?});"))
+    .unwrap();
+  assert!(export_a < require_b);
+  assert!(result.code.contains(&format!("const _a = require({a:?});")));
+  assert!(result.code.contains("const b = _a.a.name;"));
+  assert!(result.code.contains("return _b.b;"));
+}
+
+#[test]
+fn test_bundle_typescript() {
+  let fixture = crate::test_utils::Fixture::new(&[(
+    "tokens.ts",
+    r#"
+      type Size = number;
+      export const size: Size = 4;
+    "#,
+  )]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import { size } from "./tokens";
+    export const classes: string = `gap-${size as number}`;
+  "#;
+
+  let result = bundle(
+    filename,
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    None,
+  );
+
+  assert_eq!(result.graph.modules.len(), 2);
+  assert!(result.code.contains("const size = 4;"));
+  assert!(result
+    .code
+    .contains("const classes = `gap-${_tokens.size}`;"));
+  assert!(!result.code.contains("Size"));
+  assert!(!result.code.contains(": string"));
+}
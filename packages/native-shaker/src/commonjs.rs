@@ -0,0 +1,646 @@
+use crate::export_expand::is_type_only_declaration;
+use oxc_allocator::{Box as ArenaBox, Vec as ArenaVec};
+use oxc_ast::ast::{
+  Argument, AssignmentOperator, AssignmentTarget, BindingIdentifier, BindingPatternKind,
+  Declaration, ExportAllDeclaration, ExportNamedDeclaration, Expression, IdentifierReference,
+  ImportDeclaration, ImportDeclarationSpecifier, MemberExpression, ObjectProperty, Program,
+  SimpleAssignmentTarget, Statement, TSTypeAnnotation, TSTypeParameterInstantiation,
+  VariableDeclarationKind,
+};
+use oxc_ast::syntax_directed_operations::BoundNames;
+use oxc_ast::visit::walk_mut;
+use oxc_ast::{AstBuilder, Visit, VisitMut};
+use oxc_semantic::{ReferenceId, Semantic};
+use oxc_span::{Atom, Span, SPAN};
+use std::collections::{HashMap, HashSet};
+
+/// Names of the module runtime bindings, they match arguments of the `__wd40_module` wrapper.
+const REQUIRE: &str = "require";
+const EXPORTS: &str = "exports";
+const EXPORT_STAR: &str = "export_star";
+const EXPORT_BINDING: &str = "export_binding";
+
+fn is_identifier_name(name: &str) -> bool {
+  let mut chars = name.chars();
+
+  chars
+    .next()
+    .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+    && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Collects names of all bindings & references, generated module variables must not shadow them.
+#[derive(Default)]
+struct NameCollector {
+  names: HashSet<String>,
+}
+
+impl<'a> Visit<'a> for NameCollector {
+  fn visit_binding_identifier(&mut self, ident: &BindingIdentifier<'a>) {
+    self.names.insert(ident.name.to_string());
+  }
+
+  fn visit_identifier_reference(&mut self, ident: &IdentifierReference<'a>) {
+    self.names.insert(ident.name.to_string());
+  }
+}
+
+/// A variable for the module object of an import source, i.e. `_tokens` for `"./tokens"`.
+fn module_variable_name(source: &str, names: &mut HashSet<String>) -> String {
+  let basename = source
+    .rsplit('/')
+    .find(|segment| segment.chars().any(char::is_alphanumeric))
+    .unwrap_or("module");
+  let base: String = std::iter::once('_')
+    .chain(basename.chars().map(|c| {
+      if c.is_alphanumeric() || c == '_' || c == '$' {
+        c
+      } else {
+        '_'
+      }
+    }))
+    .collect();
+
+  let mut name = base.clone();
+  let mut index = 1;
+
+  while names.contains(&name) {
+    index += 1;
+    name = format!("{base}{index}");
+  }
+
+  names.insert(name.clone());
+  name
+}
+
+/// A binding of an imported module, `_a.a` for `import { a as b } from "./a"`.
+struct ImportedBinding {
+  module: String,
+  imported: String,
+}
+
+/// Lowers imports & exports to `require()` calls and assignments to `exports`, the output runs in
+/// the module wrapper of the evaluator. Import requests are replaced with resolved paths when they
+/// can be resolved.
+///
+/// `ExportReplacer` has already normalized imports & exports to one specifier per statement:
+/// - `import { a as b } from "./a"` becomes `const _a = require("./a")` and references to `b`
+///   become `_a.a`, they read the binding when evaluated like ES modules do, so cycles work
+/// - `import * as ns from "./a"` becomes `const ns = require("./a")`
+/// - `export { a as b }` becomes `exports.b = a`, hoisted to the top for function declarations
+/// - `export { b }` of an imported `b` becomes `export_binding(_a, "a", exports, "b")`, a getter
+/// - `export * from "./a"` becomes `export_star(require("./a"), exports)`
+pub(crate) struct CommonJsLowering<'a, 'r> {
+  pub ast: AstBuilder<'a>,
+
+  resolve_path: &'r dyn Fn(&str) -> Option<String>,
+  /// Module variables by import sources.
+  modules: HashMap<String, String>,
+  /// Imported bindings by their local names.
+  imports: HashMap<String, ImportedBinding>,
+  /// Local names of imported bindings by their references.
+  references: HashMap<ReferenceId, String>,
+  /// Functions declared in the module scope, they are initialized before the module body runs.
+  functions: HashSet<String>,
+}
+
+impl<'a, 'r> CommonJsLowering<'a, 'r> {
+  pub fn new(
+    ast: AstBuilder<'a>,
+    resolve_path: &'r dyn Fn(&str) -> Option<String>,
+    program: &Program<'a>,
+    semantic: &Semantic<'a>,
+  ) -> Self {
+    let mut collector = NameCollector::default();
+    collector.visit_program(program);
+    let mut names = collector.names;
+
+    let mut modules = HashMap::new();
+    let mut imports = HashMap::new();
+    let mut references = HashMap::new();
+    let mut functions = HashSet::new();
+
+    for stmt in &program.body {
+      let decl = match stmt {
+        Statement::ImportDeclaration(decl) if !decl.import_kind.is_type() => decl,
+        Statement::FunctionDeclaration(function) => {
+          functions.extend(function.id.as_ref().map(|id| id.name.to_string()));
+          continue;
+        }
+        Statement::ExportNamedDeclaration(decl) => {
+          if let Some(Declaration::FunctionDeclaration(function)) = &decl.declaration {
+            functions.extend(function.id.as_ref().map(|id| id.name.to_string()));
+          }
+          continue;
+        }
+        _ => continue,
+      };
+
+      for specifier in decl.specifiers.iter().flatten() {
+        let (imported, local) = match specifier {
+          ImportDeclarationSpecifier::ImportSpecifier(specifier)
+            if !specifier.import_kind.is_type() =>
+          {
+            (specifier.imported.name(), &specifier.local)
+          }
+          ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+            (Atom::from("default"), &specifier.local)
+          }
+          _ => continue,
+        };
+
+        let module = modules
+          .entry(decl.source.value.to_string())
+          .or_insert_with(|| module_variable_name(&decl.source.value, &mut names))
+          .clone();
+
+        if let Some(symbol_id) = local.symbol_id.get() {
+          for reference_id in semantic.symbols().get_resolved_reference_ids(symbol_id) {
+            references.insert(*reference_id, local.name.to_string());
+          }
+        }
+
+        imports.insert(
+          local.name.to_string(),
+          ImportedBinding {
+            module,
+            imported: imported.to_string(),
+          },
+        );
+      }
+    }
+
+    Self {
+      ast,
+      resolve_path,
+      modules,
+      imports,
+      references,
+      functions,
+    }
+  }
+
+  pub fn build(&self, program: &mut Program<'a>) {
+    let body = std::mem::replace(&mut program.body, self.ast.vec());
+    let mut hoisted = self.ast.vec();
+    let mut lowered = self.ast.vec();
+    let mut required = HashSet::new();
+
+    for stmt in body {
+      match stmt {
+        Statement::ImportDeclaration(decl) => self.lower_import(&decl, &mut lowered, &mut required),
+        Statement::ExportNamedDeclaration(decl) => {
+          self.lower_export(decl, &mut lowered, &mut hoisted);
+        }
+        Statement::ExportAllDeclaration(decl) => self.lower_export_all(&decl, &mut lowered),
+        stmt => lowered.push(stmt),
+      }
+    }
+
+    hoisted.extend(lowered);
+    program.body = hoisted;
+
+    ImportReferenceReplacer { lowering: self }.visit_program(program);
+  }
+
+  fn require_call(&self, source: &str) -> Expression<'a> {
+    let path = (self.resolve_path)(source).unwrap_or_else(|| source.to_string());
+    let argument = self
+      .ast
+      .expression_string_literal(SPAN, self.ast.atom(&path));
+
+    self.ast.expression_call(
+      SPAN,
+      self.ast.expression_identifier_reference(SPAN, REQUIRE),
+      Option::<TSTypeParameterInstantiation>::None,
+      self.ast.vec1(Argument::from(argument)),
+      false,
+    )
+  }
+
+  fn const_declaration(
+    &self,
+    span: Span,
+    id: BindingPatternKind<'a>,
+    init: Expression<'a>,
+  ) -> Statement<'a> {
+    let binding_pattern = self
+      .ast
+      .binding_pattern(id, Option::<TSTypeAnnotation>::None, false);
+    let variable_declarator = self.ast.variable_declarator(
+      SPAN,
+      VariableDeclarationKind::Const,
+      binding_pattern,
+      Some(init),
+      false,
+    );
+    let var_declaration = self.ast.variable_declaration(
+      span,
+      VariableDeclarationKind::Const,
+      self.ast.vec1(variable_declarator),
+      false,
+    );
+
+    self.ast.declaration_from_variable(var_declaration).into()
+  }
+
+  /// `object.name`, or `object["name"]` for names that are not valid identifiers.
+  fn member(&self, object: Expression<'a>, name: &str) -> MemberExpression<'a> {
+    let name = self.ast.atom(name);
+
+    if is_identifier_name(&name) {
+      self
+        .ast
+        .member_expression_static(SPAN, object, self.ast.identifier_name(SPAN, name), false)
+    } else {
+      self.ast.member_expression_computed(
+        SPAN,
+        object,
+        self.ast.expression_string_literal(SPAN, name),
+        false,
+      )
+    }
+  }
+
+  /// `_a.a` for an imported binding.
+  fn imported_member(&self, binding: &ImportedBinding) -> MemberExpression<'a> {
+    let module = self
+      .ast
+      .expression_identifier_reference(SPAN, self.ast.atom(&binding.module));
+
+    self.member(module, &binding.imported)
+  }
+
+  fn export_assignment(&self, span: Span, exported: &str, value: Expression<'a>) -> Statement<'a> {
+    let exports = self.ast.expression_identifier_reference(SPAN, EXPORTS);
+    let member = self.member(exports, exported);
+    let target = AssignmentTarget::from(SimpleAssignmentTarget::from(member));
+
+    self.ast.statement_expression(
+      span,
+      self
+        .ast
+        .expression_assignment(SPAN, AssignmentOperator::Assign, target, value),
+    )
+  }
+
+  /// `export_binding(_a, "a", exports, "b")`, defines a getter so the export stays live.
+  fn export_binding(&self, span: Span, exported: &str, binding: &ImportedBinding) -> Statement<'a> {
+    let call = self.ast.expression_call(
+      SPAN,
+      self
+        .ast
+        .expression_identifier_reference(SPAN, EXPORT_BINDING),
+      Option::<TSTypeParameterInstantiation>::None,
+      self.ast.vec_from_iter([
+        Argument::from(
+          self
+            .ast
+            .expression_identifier_reference(SPAN, self.ast.atom(&binding.module)),
+        ),
+        Argument::from(
+          self
+            .ast
+            .expression_string_literal(SPAN, self.ast.atom(&binding.imported)),
+        ),
+        Argument::from(self.ast.expression_identifier_reference(SPAN, EXPORTS)),
+        Argument::from(
+          self
+            .ast
+            .expression_string_literal(SPAN, self.ast.atom(exported)),
+        ),
+      ]),
+      false,
+    );
+
+    self.ast.statement_expression(span, call)
+  }
+
+  fn lower_import(
+    &self,
+    decl: &ImportDeclaration<'a>,
+    body: &mut ArenaVec<'a, Statement<'a>>,
+    required: &mut HashSet<String>,
+  ) {
+    if decl.import_kind.is_type() {
+      return;
+    }
+
+    let source = decl.source.value.as_str();
+    let Some(specifiers) = decl
+      .specifiers
+      .as_ref()
+      .filter(|specifiers| !specifiers.is_empty())
+    else {
+      // Bare imports are kept for their side effects
+      body.push(
+        self
+          .ast
+          .statement_expression(decl.span, self.require_call(source)),
+      );
+      return;
+    };
+
+    for specifier in specifiers {
+      match specifier {
+        ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+          let id = self.ast.binding_pattern_kind_from_binding_identifier(
+            self
+              .ast
+              .binding_identifier(specifier.local.span, specifier.local.name.clone()),
+          );
+
+          body.push(self.const_declaration(specifier.span, id, self.require_call(source)));
+        }
+        ImportDeclarationSpecifier::ImportSpecifier(specifier)
+          if specifier.import_kind.is_type() => {}
+        // The module object is required once, references to bindings read from it
+        _ => {
+          let Some(module) = self.modules.get(source) else {
+            continue;
+          };
+
+          if required.insert(module.clone()) {
+            let id = self.ast.binding_pattern_kind_from_binding_identifier(
+              self.ast.binding_identifier(SPAN, self.ast.atom(module)),
+            );
+
+            body.push(self.const_declaration(decl.span, id, self.require_call(source)));
+          }
+        }
+      }
+    }
+  }
+
+  fn lower_export(
+    &self,
+    mut decl: ArenaBox<'a, ExportNamedDeclaration<'a>>,
+    body: &mut ArenaVec<'a, Statement<'a>>,
+    hoisted: &mut ArenaVec<'a, Statement<'a>>,
+  ) {
+    if decl.export_kind.is_type() {
+      return;
+    }
+
+    // Declarations that `ExportReplacer` could not split are kept and their bindings exported
+    if let Some(declaration) = decl.declaration.take() {
+      let mut names = Vec::new();
+
+      if !is_type_only_declaration(&declaration) {
+        declaration.bound_names(&mut |ident| names.push((ident.span, ident.name.clone())));
+      }
+
+      let is_function = matches!(declaration, Declaration::FunctionDeclaration(_));
+      body.push(declaration.into());
+
+      for (span, name) in names {
+        let value = self.ast.expression_identifier_reference(SPAN, name.clone());
+        let assignment = self.export_assignment(span, &name, value);
+
+        if is_function {
+          hoisted.push(assignment);
+        } else {
+          body.push(assignment);
+        }
+      }
+    }
+
+    for specifier in &decl.specifiers {
+      if specifier.export_kind.is_type() {
+        continue;
+      }
+
+      let local = specifier.local.name();
+      let exported = specifier.exported.name();
+
+      match &decl.source {
+        // `export { a as b } from "./a"` reads the binding from the required module
+        Some(source) => {
+          let value =
+            Expression::from(self.member(self.require_call(source.value.as_str()), &local));
+
+          body.push(self.export_assignment(specifier.span, &exported, value));
+        }
+        None => match self.imports.get(local.as_str()) {
+          Some(binding) => body.push(self.export_binding(specifier.span, &exported, binding)),
+          // Functions can be called by modules in a cycle before the export statement runs
+          None if self.functions.contains(local.as_str()) => {
+            let value = self.ast.expression_identifier_reference(SPAN, local);
+
+            hoisted.push(self.export_assignment(specifier.span, &exported, value));
+          }
+          None => {
+            let value = self.ast.expression_identifier_reference(SPAN, local);
+
+            body.push(self.export_assignment(specifier.span, &exported, value));
+          }
+        },
+      }
+    }
+  }
+
+  fn lower_export_all(
+    &self,
+    decl: &ExportAllDeclaration<'a>,
+    body: &mut ArenaVec<'a, Statement<'a>>,
+  ) {
+    if decl.export_kind.is_type() {
+      return;
+    }
+
+    let required = self.require_call(decl.source.value.as_str());
+
+    // `export * as ns from "./a"` is a plain named export of the whole module
+    if let Some(exported) = &decl.exported {
+      body.push(self.export_assignment(decl.span, &exported.name(), required));
+      return;
+    }
+
+    let call = self.ast.expression_call(
+      SPAN,
+      self.ast.expression_identifier_reference(SPAN, EXPORT_STAR),
+      Option::<TSTypeParameterInstantiation>::None,
+      self.ast.vec_from_iter([
+        Argument::from(required),
+        Argument::from(self.ast.expression_identifier_reference(SPAN, EXPORTS)),
+      ]),
+      false,
+    );
+
+    body.push(self.ast.statement_expression(decl.span, call));
+  }
+}
+
+/// Replaces references to imported bindings with members of module variables, `b` of
+/// `import { a as b } from "./a"` becomes `_a.a`.
+struct ImportReferenceReplacer<'l, 'a, 'r> {
+  lowering: &'l CommonJsLowering<'a, 'r>,
+}
+
+impl<'l, 'a, 'r> ImportReferenceReplacer<'l, 'a, 'r> {
+  fn imported_binding(&self, expr: &Expression<'a>) -> Option<&'l ImportedBinding> {
+    let Expression::Identifier(ident) = expr else {
+      return None;
+    };
+    let local = self.lowering.references.get(&ident.reference_id.get()?)?;
+
+    self.lowering.imports.get(local)
+  }
+}
+
+impl<'l, 'a, 'r> VisitMut<'a> for ImportReferenceReplacer<'l, 'a, 'r> {
+  fn visit_expression(&mut self, expr: &mut Expression<'a>) {
+    if let Some(binding) = self.imported_binding(expr) {
+      *expr = Expression::from(self.lowering.imported_member(binding));
+      return;
+    }
+
+    walk_mut::walk_expression(self, expr);
+  }
+
+  fn visit_object_property(&mut self, property: &mut ObjectProperty<'a>) {
+    // `{ b }` becomes `{ b: _a.a }`
+    if property.shorthand && self.imported_binding(&property.value).is_some() {
+      property.shorthand = false;
+    }
+
+    walk_mut::walk_object_property(self, property);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use oxc_allocator::Allocator;
+  use pretty_assertions::assert_eq;
+
+  use crate::transformer::{transform_inner, TransformInnerOptions};
+
+  pub fn assert_fixture(input: &str, expected: &str, only_exports: Vec<&str>) {
+    let allocator = Allocator::default();
+    let result = transform_inner(
+      &allocator,
+      &"source.js".to_string(),
+      &input.to_string(),
+      only_exports,
+      TransformInnerOptions {
+        commonjs: true,
+        ..TransformInnerOptions::default()
+      },
+    );
+    let result_code = result.output.replace("\t", "  ");
+
+    assert_eq!(result.errors.len(), 0);
+    assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
+  }
+}
+
+#[test]
+
+fn test_imports() {
+  let input = r#"
+    import { foo } from "./foo";
+    import { bar as baz } from "./bar";
+    import qux from "./qux";
+    import * as utils from "./utils";
+    import "./polyfill";
+    export const classes = foo + baz + qux + utils.gap;
+  "#;
+  let output = r#"
+    const _foo = require("./foo");
+    const _bar = require("./bar");
+    const _qux = require("./qux");
+    const utils = require("./utils");
+    require("./polyfill");
+    const classes = _foo.foo + _bar.bar + _qux.default + utils.gap;
+    exports.classes = classes;
+  "#;
+
+  tests::assert_fixture(input, output, vec!["classes"]);
+}
+
+#[test]
+
+fn test_exports() {
+  let input = r#"
+    const foo = "foo";
+    export { foo, foo as default, foo as "foo-bar" };
+    export { gap as spacing } from "./tokens";
+    export * from "./colors";
+    export * as icons from "./icons";
+  "#;
+  let output = r#"
+    const foo = "foo";
+    exports.foo = foo;
+    exports.default = foo;
+    exports["foo-bar"] = foo;
+    const _tokens = require("./tokens");
+    export_binding(_tokens, "gap", exports, "spacing");
+    export_star(require("./colors"), exports);
+    exports.icons = require("./icons");
+  "#;
+
+  tests::assert_fixture(input, output, vec!["*"]);
+}
+
+#[test]
+
+fn test_import_references() {
+  let input = r#"
+    import { gap, spacing as space } from "./tokens";
+    const _tokens = "shadowed";
+    function getSpacing(gap) {
+      return gap + space;
+    }
+    export { getSpacing };
+    export const classes = { gap, space, _tokens };
+  "#;
+  let output = r#"
+    exports.getSpacing = getSpacing;
+    const _tokens2 = require("./tokens");
+    const _tokens = "shadowed";
+    function getSpacing(gap) {
+      return gap + _tokens2.spacing;
+    }
+    const classes = { gap: _tokens2.gap, space: _tokens2.spacing, _tokens };
+    exports.classes = classes;
+  "#;
+
+  tests::assert_fixture(input, output, vec!["*"]);
+}
+
+#[test]
+
+fn test_resolved_paths() {
+  use crate::transformer::{transform_inner, TransformInnerOptions};
+  use oxc_allocator::Allocator;
+  use oxc_resolver::{ResolveOptions, Resolver};
+
+  let fixture = crate::test_utils::Fixture::new(&[("tokens.ts", "export const gap = 1;")]);
+  let root = fixture.path();
+
+  let resolver = Resolver::new(ResolveOptions {
+    extensions: vec![".ts".to_string()],
+    ..ResolveOptions::default()
+  });
+  let filename = root.join("source.ts").to_string_lossy().to_string();
+  let allocator = Allocator::default();
+  let result = transform_inner(
+    &allocator,
+    &filename,
+    &r#"export { gap } from "./tokens";"#.to_string(),
+    vec!["gap"],
+    TransformInnerOptions {
+      commonjs: true,
+      resolver: Some(&resolver),
+      ..TransformInnerOptions::default()
+    },
+  );
+
+  assert!(result.output.contains(&format!(
+    "const _tokens = require({:?});",
+    root.join("tokens.ts").to_string_lossy()
+  )));
+  assert!(result
+    .output
+    .contains(r#"export_binding(_tokens, "gap", exports, "gap");"#));
+}
@@ -87,6 +87,14 @@ impl<'a> TransformContext<'a> {
     codegen
   }
 
+  /// Same as [`Self::codegen`], but also produces a source map that points to the source text of
+  /// this context.
+  pub fn codegen_with_source_map(&self) -> Codegen<'a> {
+    self
+      .codegen()
+      .enable_source_map(self.filename, self.source_text)
+  }
+
   pub fn add_diagnostics(&self, diagnostics: Vec<OxcDiagnostic>) {
     if diagnostics.is_empty() {
       return;
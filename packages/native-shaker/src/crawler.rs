@@ -1,93 +1,853 @@
-use crate::module_resolve::resolve;
-use crate::transform_inner;
+use crate::module_resolve::{create_resolver, resolve, Assets, ModuleResolver};
+use crate::{
+  transform_inner, AssetOptions, BareImportsPolicy, TransformInnerOptions, TransformOptions,
+};
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Task};
 use napi_derive::napi;
 use oxc_allocator::Allocator;
-use oxc_resolver::{ResolveOptions, Resolver};
+use oxc_diagnostics::{Error, OxcDiagnostic};
+use oxc_resolver::Resolver;
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
+use std::sync::Arc;
 
-struct Crawler {
-  allocator: Allocator,
-  resolver: Resolver,
+/// An import of a crawled module and the module it resolves to.
+#[napi(object)]
+#[derive(Debug)]
+pub struct ModuleDependency {
+  /// Import request as written in the code, i.e. `./tokens`.
+  pub source: String,
+  /// Names used from the imported module, the module is shaken to them. Property paths, i.e.
+  /// `tokens.colorBrand`, are used when only properties of an export are read.
+  pub imported_names: Vec<String>,
+  /// Resolved path of the imported module, not present when the request can't be resolved.
+  pub path: Option<String>,
+  /// Assets (images, fonts, styles) are not JavaScript modules and are not crawled.
+  pub is_asset: bool,
+  pub error: Option<String>,
+}
+
+/// A module of the graph shaken to exports requested by its importers.
+#[napi(object)]
+#[derive(Debug)]
+pub struct CrawledModule {
+  /// Resolved path of the module.
+  pub path: String,
+  /// Exports requested from the module.
+  pub only_exports: Vec<String>,
+  /// Shaken code of the module.
+  pub code: String,
+  pub dependencies: Vec<ModuleDependency>,
+  /// Errors of reading & shaking the module.
+  pub errors: Vec<String>,
+}
+
+#[napi(object)]
+#[derive(Debug)]
+pub struct ModuleGraph {
+  /// Crawled modules in the order they were discovered, the entry module is the first one. Every
+  /// path is present once.
+  pub modules: Vec<CrawledModule>,
+  /// Warnings about the graph itself, i.e. circular dependencies.
+  pub diagnostics: Vec<String>,
+}
+
+/// Shakes modules in parallel, every worker thread uses its own allocator while the resolver and
+/// its cache are shared.
+pub(crate) struct Crawler {
+  resolver: Arc<Resolver>,
+  /// Replaces imports of assets with placeholders when set, otherwise they are kept as imports.
+  assets: Option<Assets>,
+  /// Classifies assets when `assets` is not set, the same way the resolver handle does.
+  resolver_assets: Assets,
+  /// Lower shaken modules to CommonJS for the bundle, imports of assets are replaced with
+  /// placeholders and bare imports of assets are dropped as they can't be evaluated.
+  commonjs: bool,
 }
 
 impl Crawler {
-  pub fn new() -> Self {
-    let allocator = Allocator::default();
-
-    let resolve_options = ResolveOptions {
-      extensions: vec![
-        ".js".into(),
-        ".jsx".into(),
-        ".ts".into(),
-        ".tsx".into(),
-        ".json".into(),
-        ".mjs".into(),
-        ".cjs".into(),
-        ".mts".into(),
-        ".cts".into(),
-      ],
-      exports_fields: vec![vec!["import".into()]],
-      main_fields: vec!["module".into(), "main".into()],
-      ..ResolveOptions::default()
+  pub fn new(resolver: &ModuleResolver, assets: Option<Assets>, commonjs: bool) -> Self {
+    Self {
+      resolver: resolver.resolver(),
+      assets,
+      resolver_assets: resolver.assets().clone(),
+      commonjs,
+    }
+  }
+
+  fn is_asset(&self, path: &str) -> bool {
+    self
+      .assets
+      .as_ref()
+      .unwrap_or(&self.resolver_assets)
+      .is_asset(path)
+  }
+
+  fn shake_module(
+    &self,
+    allocator: &Allocator,
+    path: &String,
+    source_text: &String,
+    only_exports: &[String],
+  ) -> CrawledModule {
+    let options = if self.commonjs {
+      TransformInnerOptions {
+        resolver: Some(&self.resolver),
+        bare_imports: BareImportsPolicy::KeepUnlessAsset,
+        assets: Some(self.assets.clone().unwrap_or_default()),
+        commonjs: true,
+        // types & JSX can't be evaluated
+        transpile: Some(TransformOptions::default().into()),
+        ..TransformInnerOptions::default()
+      }
+    } else {
+      TransformInnerOptions {
+        resolver: Some(&self.resolver),
+        assets: self.assets.clone(),
+        ..TransformInnerOptions::default()
+      }
     };
-    let resolver = Resolver::new(resolve_options);
+    let result = transform_inner(
+      allocator,
+      path,
+      source_text,
+      only_exports.iter().map(String::as_str).collect(),
+      options,
+    );
+
+    let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+    let dependencies = result
+      .imports
+      .into_iter()
+      .map(|import| {
+        let resolution = resolve(&self.resolver, directory, &import.source);
 
-    Self { allocator, resolver }
+        ModuleDependency {
+          is_asset: resolution
+            .path
+            .as_deref()
+            .is_some_and(|path| self.is_asset(path)),
+          source: import.source,
+          imported_names: import.names,
+          path: resolution.path,
+          error: resolution.error,
+        }
+      })
+      .collect();
+
+    CrawledModule {
+      path: path.clone(),
+      only_exports: only_exports.to_vec(),
+      code: result.output,
+      dependencies,
+      errors: result.errors,
+    }
   }
 
-  fn crawl_by_path(&self, path: &String, only_exports: Vec<&str>) {
-    match fs::read_to_string(path) {
-      Ok(module_text) => {
-        let result = transform_inner(&self.allocator, path, &module_text, only_exports, false);
+  /// Reads a module if it was not read yet and shakes it to its requested exports. Returns the
+  /// source text when it was read, so it can be stored in the graph.
+  fn load_and_shake_module(
+    &self,
+    allocator: &Allocator,
+    node: &ModuleNode,
+  ) -> (Option<String>, CrawledModule) {
+    let only_exports: Vec<String> = node.only_exports.iter().cloned().collect();
+
+    if let Some(source_text) = &node.source_text {
+      return (
+        None,
+        self.shake_module(allocator, &node.path, source_text, &only_exports),
+      );
+    }
 
-        println!("{:?}", module_text);
+    match fs::read_to_string(&node.path) {
+      Ok(source_text) => {
+        let module = self.shake_module(allocator, &node.path, &source_text, &only_exports);
+        (Some(source_text), module)
       }
-      Err(err) => {
-        println!("{:?}", err);
+      Err(err) => (
+        None,
+        CrawledModule {
+          path: node.path.clone(),
+          only_exports,
+          code: String::new(),
+          dependencies: Vec::new(),
+          errors: vec![err.to_string()],
+        },
+      ),
+    }
+  }
+
+  /// Shakes the entry module and then its dependencies to names imported from them, recursively.
+  /// Every module is shaken to the union of exports requested by all its importers, it's shaken
+  /// again only when the union grows.
+  pub fn crawl(
+    &self,
+    filename: String,
+    source_text: String,
+    only_exports: Vec<String>,
+  ) -> ModuleGraph {
+    let all_exports = "*".to_string();
+    let mut graph = ModuleGraphBuilder::default();
+    let mut queue: Vec<usize> = graph
+      .request(&filename, Some(source_text), &only_exports)
+      .into_iter()
+      .collect();
+
+    // Modules are shaken in waves: all queued modules in parallel, then their dependencies
+    while !queue.is_empty() {
+      let results: Vec<(usize, Option<String>, CrawledModule)> = queue
+        .par_iter()
+        .map_init(Allocator::default, |allocator, index| {
+          let (source_text, module) = self.load_and_shake_module(allocator, &graph.nodes[*index]);
+          (*index, source_text, module)
+        })
+        .collect();
+
+      let mut next_queue = Vec::new();
+
+      for (index, source_text, module) in results {
+        for dependency in &module.dependencies {
+          let (Some(path), false) = (&dependency.path, dependency.is_asset) else {
+            continue;
+          };
+
+          // Bare imports, i.e. `import "./polyfill"`, import no names, but the whole module is
+          // evaluated for its side effects
+          let names = if dependency.imported_names.is_empty() {
+            std::slice::from_ref(&all_exports)
+          } else {
+            dependency.imported_names.as_slice()
+          };
+          next_queue.extend(graph.request(path, None, names));
+        }
+
+        let node = &mut graph.nodes[index];
+        if source_text.is_some() {
+          node.source_text = source_text;
+        }
+        node.module = Some(module);
       }
+
+      next_queue.sort_unstable();
+      next_queue.dedup();
+      queue = next_queue;
     }
+
+    graph.build()
   }
+}
 
-  pub fn start_crawl(&self, source_text: String) {
-    let source_text = r#"
-        import { makeStyles } from '@griffel/core';
+struct ModuleNode {
+  path: String,
+  /// Read lazily, kept to shake the module again without reading it.
+  source_text: Option<String>,
+  /// Union of exports requested by importers, `*` replaces all other names.
+  only_exports: BTreeSet<String>,
+  /// Result of the last shaking.
+  module: Option<CrawledModule>,
+}
 
-        import tokens from './tokens';
+/// Modules keyed by resolved path.
+#[derive(Default)]
+struct ModuleGraphBuilder {
+  nodes: Vec<ModuleNode>,
+  indexes: HashMap<String, usize>,
+}
 
-        export const classes = makeStyles({
-            root: {
-                color: tokens.color,
-            },
+impl ModuleGraphBuilder {
+  /// Adds requested exports to a module. Returns the index of the module when it has to be shaken:
+  /// it's new or its set of requested exports has grown.
+  fn request(
+    &mut self,
+    path: &str,
+    source_text: Option<String>,
+    names: &[String],
+  ) -> Option<usize> {
+    let (index, is_new) = match self.indexes.get(path) {
+      Some(index) => (*index, false),
+      None => {
+        self.nodes.push(ModuleNode {
+          path: path.to_string(),
+          source_text,
+          only_exports: BTreeSet::new(),
+          module: None,
         });
-        "#;
-    let path = PathBuf::from(
-      "/Users/olfedias/WebstormProjects/wd40/packages/transform/__fixtures__/import-default",
-    );
-    let requests = vec!["@griffel/core", "./tokens"];
+        self.indexes.insert(path.to_string(), self.nodes.len() - 1);
+
+        (self.nodes.len() - 1, true)
+      }
+    };
+
+    let only_exports = &mut self.nodes[index].only_exports;
+    let has_grown = if only_exports.contains("*") {
+      false
+    } else if names.iter().any(|name| name == "*") {
+      *only_exports = BTreeSet::from(["*".to_string()]);
+      true
+    } else {
+      let len = only_exports.len();
+      only_exports.extend(names.iter().cloned());
+      only_exports.len() > len
+    };
+
+    (is_new || has_grown).then_some(index)
+  }
+
+  /// Edges between modules of the graph, assets & unresolved imports are skipped.
+  fn edges(&self) -> Vec<Vec<usize>> {
+    self
+      .nodes
+      .iter()
+      .map(|node| {
+        node
+          .module
+          .iter()
+          .flat_map(|module| &module.dependencies)
+          .filter(|dependency| !dependency.is_asset)
+          .filter_map(|dependency| self.indexes.get(dependency.path.as_ref()?).copied())
+          .collect()
+      })
+      .collect()
+  }
+
+  /// Finds import cycles, each one is reported once by the import that closes it. The graph is
+  /// walked with an explicit stack, long import chains would overflow the thread stack otherwise.
+  fn find_cycles(&self) -> Vec<Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+      New,
+      InStack,
+      Done,
+    }
+
+    let edges = self.edges();
+    let mut states = vec![State::New; self.nodes.len()];
+    let mut cycles = Vec::new();
 
-    for request in requests {
-      let result = resolve(&self.resolver, &path, request);
-      println!("{:?}", result);
+    for root in 0..self.nodes.len() {
+      if states[root] != State::New {
+        continue;
+      }
+
+      // Modules of the current import chain & positions of their next edges to visit
+      let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+      states[root] = State::InStack;
 
-      if let Some(path) = result.path {
-        let only_exports = vec!["default"];
+      while let Some((index, edge_position)) = stack.last_mut() {
+        let index = *index;
+        let Some(next) = edges[index].get(*edge_position).copied() else {
+          states[index] = State::Done;
+          stack.pop();
+          continue;
+        };
+        *edge_position += 1;
 
-        self.crawl_by_path(&path, only_exports);
+        match states[next] {
+          State::New => {
+            states[next] = State::InStack;
+            stack.push((next, 0));
+          }
+          State::InStack => {
+            let start = stack.iter().position(|(index, _)| *index == next).unwrap();
+            cycles.push(stack[start..].iter().map(|(index, _)| *index).collect());
+          }
+          State::Done => {}
+        }
       }
     }
+
+    cycles
+  }
+
+  fn build(self) -> ModuleGraph {
+    let diagnostics = self
+      .find_cycles()
+      .into_iter()
+      .map(|cycle| {
+        let chain = cycle
+          .iter()
+          .chain(cycle.first())
+          .map(|index| self.nodes[*index].path.as_str())
+          .collect::<Vec<_>>()
+          .join(" -> ");
+
+        let diagnostic = OxcDiagnostic::warn(format!("Circular dependency: {chain}"))
+          .with_help("Modules of a cycle are evaluated before their imports are initialized");
+        format!("{:?}", Error::from(diagnostic))
+      })
+      .collect();
+
+    ModuleGraph {
+      modules: self
+        .nodes
+        .into_iter()
+        .filter_map(|node| node.module)
+        .collect(),
+      diagnostics,
+    }
+  }
+}
+
+/// Shakes a module to requested exports and crawls its dependencies, each of them is shaken to
+/// names that are imported from it.
+///
+/// @param filename Path of the entry module, imports are resolved relative to it.
+/// @param sourceText Code of the entry module.
+/// @param onlyExports Names of exports that should be kept in the entry module.
+/// @param resolver A resolver from {@link createResolver}, its cache is shared between crawls.
+/// A resolver with default options is used if omitted.
+/// @param assets Replaces imports of assets with placeholders of their paths when set, otherwise
+/// they are kept as imports.
+///
+/// @returns shaken modules & their resolved dependencies.
+#[napi]
+pub fn crawl(
+  filename: String,
+  source_text: String,
+  only_exports: Vec<String>,
+  resolver: Option<&ModuleResolver>,
+  assets: Option<AssetOptions>,
+) -> ModuleGraph {
+  let crawler = Crawler::new(&shared_resolver(resolver), assets.map(Into::into), false);
+
+  crawler.crawl(filename, source_text, only_exports)
+}
+
+pub(crate) fn shared_resolver(resolver: Option<&ModuleResolver>) -> ModuleResolver {
+  resolver
+    .cloned()
+    .unwrap_or_else(|| create_resolver(None, None))
+}
+
+pub struct CrawlTask {
+  filename: String,
+  source_text: String,
+  only_exports: Vec<String>,
+  resolver: ModuleResolver,
+  assets: Option<Assets>,
+}
+
+#[napi]
+impl Task for CrawlTask {
+  type Output = ModuleGraph;
+  type JsValue = ModuleGraph;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    let crawler = Crawler::new(&self.resolver, self.assets.take(), false);
+
+    Ok(crawler.crawl(
+      std::mem::take(&mut self.filename),
+      std::mem::take(&mut self.source_text),
+      std::mem::take(&mut self.only_exports),
+    ))
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(output)
   }
 }
 
+/// Same as {@link crawl}, but crawls on the libuv thread pool and doesn't block the event loop.
+///
+/// @returns a promise of shaken modules & their resolved dependencies.
 #[napi]
-pub fn start_crawl(source_text: String) {
-    let crawler = Crawler::new();
+pub fn crawl_async(
+  filename: String,
+  source_text: String,
+  only_exports: Vec<String>,
+  resolver: Option<&ModuleResolver>,
+  assets: Option<AssetOptions>,
+) -> AsyncTask<CrawlTask> {
+  AsyncTask::new(CrawlTask {
+    filename,
+    source_text,
+    only_exports,
+    resolver: shared_resolver(resolver),
+    assets: assets.map(Into::into),
+  })
+}
+
+#[test]
+fn test_crawl() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "tokens.ts",
+      r#"
+        import { palette } from "./palette";
+        export const colorBrand = palette.blue;
+        export const colorNeutral = palette.grey;
+      "#,
+    ),
+    (
+      "palette.ts",
+      r#"
+        export const palette = { blue: "blue", grey: "grey" };
+        export const unused = "unused";
+      "#,
+    ),
+    ("icon.svg", "<svg />"),
+  ]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import { colorBrand } from "./tokens";
+    import icon from "./icon.svg";
+    import { missing } from "./missing";
+    export const classes = [colorBrand, icon, missing];
+  "#;
+
+  let graph = crawl(
+    filename.clone(),
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    None,
+    None,
+  );
+  let paths: Vec<&str> = graph
+    .modules
+    .iter()
+    .map(|module| module.path.as_str())
+    .collect();
+
+  assert_eq!(
+    paths,
+    vec![
+      filename.as_str(),
+      root.join("tokens.ts").to_str().unwrap(),
+      root.join("palette.ts").to_str().unwrap(),
+    ]
+  );
+
+  let entry = &graph.modules[0];
+  assert_eq!(entry.dependencies.len(), 3);
+  assert_eq!(entry.dependencies[0].imported_names, vec!["colorBrand"]);
+  assert!(entry.dependencies[1].is_asset);
+  assert_eq!(entry.dependencies[2].path, None);
+  assert!(entry.dependencies[2].error.is_some());
+
+  let tokens = &graph.modules[1];
+  assert_eq!(tokens.only_exports, vec!["colorBrand"]);
+  assert!(!tokens.code.contains("colorNeutral"));
+  assert_eq!(tokens.dependencies[0].imported_names, vec!["palette.blue"]);
+
+  let palette = &graph.modules[2];
+  assert!(!palette.code.contains("unused"));
+  assert!(!palette.code.contains("grey"));
+  assert_eq!(palette.errors.len(), 0);
+}
+
+#[test]
+fn test_crawl_members() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    ("index.ts", r#"export * from "./tokens";"#),
+    (
+      "tokens.ts",
+      r#"
+        import { palette } from "./palette";
+        export const tokens = {
+          colorBrand: palette.blue,
+          colorNeutral: palette.grey,
+        };
+      "#,
+    ),
+    (
+      "palette.ts",
+      r#"export const palette = { blue: "blue", grey: "grey", red: "red" };"#,
+    ),
+  ]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import { tokens } from "./index";
+    export const classes = [tokens.colorBrand];
+  "#;
+
+  let graph = crawl(
+    filename,
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    None,
+    None,
+  );
+
+  assert_eq!(graph.modules.len(), 4);
+  assert_eq!(
+    graph.modules[0].dependencies[0].imported_names,
+    vec!["tokens.colorBrand"]
+  );
+
+  let index = &graph.modules[1];
+  assert_eq!(index.only_exports, vec!["tokens.colorBrand"]);
+  assert_eq!(
+    index.dependencies[0].imported_names,
+    vec!["tokens.colorBrand"]
+  );
+
+  let tokens = &graph.modules[2];
+  assert!(tokens.code.contains("colorBrand"));
+  assert!(!tokens.code.contains("colorNeutral"));
+  assert_eq!(tokens.dependencies[0].imported_names, vec!["palette.blue"]);
+
+  let palette = &graph.modules[3];
+  assert!(palette.code.contains("blue"));
+  assert!(!palette.code.contains("grey"));
+  assert!(!palette.code.contains("red"));
+}
+
+#[test]
+fn test_crawl_bare_imports() {
+  let fixture = crate::test_utils::Fixture::new(&[(
+    "polyfill.ts",
+    r#"
+      globalThis.polyfilled = true;
+      export const unused = "unused";
+    "#,
+  )]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import "./polyfill";
+    export const classes = [];
+  "#;
+
+  let graph = crawl(
+    filename,
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    None,
+    None,
+  );
+
+  assert_eq!(graph.modules.len(), 2);
+  assert_eq!(graph.modules[0].dependencies[0].imported_names.len(), 0);
 
-    crawler.start_crawl(source_text);
+  let polyfill = &graph.modules[1];
+  assert_eq!(polyfill.only_exports, vec!["*"]);
+  assert!(polyfill.code.contains("globalThis.polyfilled = true;"));
 }
 
 #[test]
-fn test_start_crawl() {
-  start_crawl("".to_string());
-  assert_eq!(1, 2)
+fn test_crawl_assets() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    ("button.style", ""),
+    ("tokens.ts", r#"export const color = "red";"#),
+  ]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import button from "./button.style";
+    import { color } from "./tokens";
+    export const classes = [button, color];
+  "#;
+
+  let graph = crawl(
+    filename,
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    None,
+    Some(AssetOptions {
+      extensions: Some(vec![".style".to_string()]),
+      ..AssetOptions::default()
+    }),
+  );
+
+  assert_eq!(graph.modules.len(), 2);
+  let entry = &graph.modules[0];
+  assert_eq!(entry.dependencies.len(), 1);
+  assert_eq!(entry.dependencies[0].source, "./tokens");
+  assert!(entry.code.contains(&format!(
+    "const button = \"@wd40:{}:@wd40\";",
+    root.join("button.style").to_string_lossy()
+  )));
+}
+
+#[test]
+fn test_crawl_resolver_assets() {
+  let fixture = crate::test_utils::Fixture::new(&[("button.style", "")]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import button from "./button.style";
+    export const classes = [button];
+  "#;
+
+  let resolver = create_resolver(
+    None,
+    Some(AssetOptions {
+      extensions: Some(vec![".style".to_string()]),
+      ..AssetOptions::default()
+    }),
+  );
+  let graph = crawl(
+    filename,
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    Some(&resolver),
+    None,
+  );
+
+  // the import is kept, but the module is classified as an asset like the resolver does
+  assert_eq!(graph.modules.len(), 1);
+  assert!(graph.modules[0].dependencies[0].is_asset);
+}
+
+#[test]
+fn test_crawl_merges_requested_exports() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "theme.ts",
+      r#"
+        import { spacing } from "./tokens";
+        export const theme = { spacing };
+      "#,
+    ),
+    (
+      "tokens.ts",
+      r#"
+        export const color = "red";
+        export const spacing = "4px";
+        export const unused = "unused";
+      "#,
+    ),
+  ]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import { color } from "./tokens";
+    import { theme } from "./theme";
+    export const classes = [color, theme];
+  "#;
+
+  let graph = crawl(
+    filename,
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    None,
+    None,
+  );
+
+  assert_eq!(graph.modules.len(), 3);
+  assert_eq!(graph.diagnostics.len(), 0);
+
+  let tokens = &graph.modules[1];
+  assert_eq!(tokens.path, root.join("tokens.ts").to_str().unwrap());
+  assert_eq!(tokens.only_exports, vec!["color", "spacing"]);
+  assert!(tokens.code.contains("spacing"));
+  assert!(!tokens.code.contains("unused"));
+}
+
+#[test]
+fn test_crawl_cycles() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "a.ts",
+      r#"
+        import { b } from "./b";
+        export const a = "a";
+        export const ab = a + b;
+      "#,
+    ),
+    (
+      "b.ts",
+      r#"
+        import { a } from "./a";
+        export const b = "b";
+        export const ba = b + a;
+      "#,
+    ),
+  ]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import { ab } from "./a";
+    import { ba } from "./b";
+    export const classes = [ab, ba];
+  "#;
+
+  let graph = crawl(
+    filename,
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    None,
+    None,
+  );
+
+  assert_eq!(graph.modules.len(), 3);
+  assert_eq!(graph.modules[1].only_exports, vec!["a", "ab"]);
+  assert_eq!(graph.modules[2].only_exports, vec!["b", "ba"]);
+  assert_eq!(graph.diagnostics.len(), 1);
+  assert!(graph.diagnostics[0].contains("Circular dependency"));
+}
+
+#[test]
+fn test_find_cycles_long_chain() {
+  const COUNT: usize = 100_000;
+
+  let mut graph = ModuleGraphBuilder::default();
+  for index in 0..COUNT {
+    let path = format!("/{index}.ts");
+    graph.request(&path, None, &[]);
+    graph.nodes[index].module = Some(CrawledModule {
+      path,
+      only_exports: Vec::new(),
+      code: String::new(),
+      dependencies: vec![ModuleDependency {
+        source: String::new(),
+        imported_names: Vec::new(),
+        path: Some(format!("/{}.ts", (index + 1) % COUNT)),
+        is_asset: false,
+        error: None,
+      }],
+      errors: Vec::new(),
+    });
+  }
+
+  let cycles = graph.find_cycles();
+  assert_eq!(cycles.len(), 1);
+  assert_eq!(cycles[0].len(), COUNT);
+}
+
+#[test]
+fn test_crawl_resolver_options() {
+  use crate::ResolverOptions;
+
+  let fixture = crate::test_utils::Fixture::new(&[
+    ("src/tokens.style", r#"export const color = "red";"#),
+    ("src/unused.ts", r#"export const color = "blue";"#),
+  ]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import { color } from "@/tokens";
+    export const classes = [color];
+  "#;
+
+  let resolver = create_resolver(
+    Some(ResolverOptions {
+      extensions: Some(vec![".style".to_string()]),
+      alias: Some(vec![(
+        "@".to_string(),
+        vec![root.join("src").to_string_lossy().to_string()],
+      )]),
+      ..ResolverOptions::default()
+    }),
+    None,
+  );
+  let graph = crawl(
+    filename,
+    source_text.to_string(),
+    vec!["classes".to_string()],
+    Some(&resolver),
+    None,
+  );
+
+  assert_eq!(graph.modules.len(), 2);
+  assert_eq!(
+    graph.modules[1].path,
+    root.join("src/tokens.style").to_str().unwrap()
+  );
 }
@@ -1,49 +1,203 @@
 use oxc_allocator::{Allocator, CloneIn, Vec as ArenaVec};
 use oxc_ast::ast::{
-  BindingIdentifier, BindingPatternKind, Declaration, ExportDefaultDeclarationKind,
-  ExportNamedDeclaration, ExportSpecifier, ImportDeclarationSpecifier, ImportOrExportKind,
-  ImportSpecifier, ModuleExportName, Program, PropertyKey, Statement, StringLiteral,
-  TSTypeAnnotation, VariableDeclarationKind, VariableDeclarator,
+  BindingIdentifier, BindingPatternKind, Declaration, ExportDefaultDeclaration,
+  ExportDefaultDeclarationKind, ExportNamedDeclaration, ExportSpecifier, Expression,
+  ImportDeclaration, ImportDeclarationSpecifier, ImportOrExportKind, ImportSpecifier,
+  ModuleExportName, NumberBase, Program, PropertyKey, PropertyKind, Statement, StringLiteral,
+  TSEnumDeclaration, TSEnumMemberName, TSModuleDeclaration, TSModuleDeclarationBody,
+  TSTypeAnnotation, UnaryOperator, VariableDeclarationKind, VariableDeclarator,
 };
 use oxc_ast::AstBuilder;
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_semantic::SymbolFlags;
 use oxc_span::Atom;
-use oxc_span::SPAN;
+use oxc_span::{GetSpan, Span, SPAN};
 use oxc_traverse::{walk_program, Traverse, TraverseCtx};
+use std::collections::HashSet;
 use std::ops::Deref;
 
+fn unsupported_syntax(description: &str, span: Span) -> OxcDiagnostic {
+  OxcDiagnostic::error(format!("Unsupported syntax: {description}"))
+    .with_help("The statement was left as is, the module can't be tree shaken safely")
+    .with_label(span)
+}
+
+/// Returns `true` for declarations that don't exist at runtime: type aliases, interfaces, ambient
+/// (`declare`) declarations, overload signatures and namespaces that contain only those.
+pub(crate) fn is_type_only_declaration(declaration: &Declaration) -> bool {
+  match declaration {
+    Declaration::TSTypeAliasDeclaration(_) | Declaration::TSInterfaceDeclaration(_) => true,
+    Declaration::VariableDeclaration(var) => var.declare,
+    Declaration::FunctionDeclaration(func) => func.declare || func.body.is_none(),
+    Declaration::ClassDeclaration(class) => class.declare,
+    Declaration::TSEnumDeclaration(decl) => decl.declare,
+    Declaration::TSModuleDeclaration(module) => is_type_only_module(module),
+    _ => false,
+  }
+}
+
+fn is_type_only_module(module: &TSModuleDeclaration) -> bool {
+  if module.declare {
+    return true;
+  }
+
+  match &module.body {
+    None => true,
+    Some(TSModuleDeclarationBody::TSModuleDeclaration(nested)) => is_type_only_module(nested),
+    Some(TSModuleDeclarationBody::TSModuleBlock(block)) => {
+      block.body.iter().all(is_type_only_statement)
+    }
+  }
+}
+
+fn is_type_only_statement(stmt: &Statement) -> bool {
+  match stmt {
+    Statement::ImportDeclaration(decl) => decl.import_kind.is_type(),
+    Statement::ExportAllDeclaration(decl) => decl.export_kind.is_type(),
+    Statement::ExportNamedDeclaration(decl) => {
+      decl.export_kind.is_type()
+        || decl
+          .declaration
+          .as_ref()
+          .is_some_and(is_type_only_declaration)
+    }
+    Statement::ExportDefaultDeclaration(decl) => matches!(
+      decl.declaration,
+      ExportDefaultDeclarationKind::TSInterfaceDeclaration(_)
+    ),
+    _ => stmt.as_declaration().is_some_and(is_type_only_declaration),
+  }
+}
+
+/// Checks if a root scope binding only exists in type space (i.e. `export { Props }` where `Props`
+/// is an interface), such exports are erased together with the declaration.
+fn is_type_only_binding(name: &str, ctx: &TraverseCtx) -> bool {
+  let Some(symbol_id) = ctx.scopes().get_root_binding(name) else {
+    return false;
+  };
+  let flags = ctx.symbols().get_flags(symbol_id);
+
+  flags.intersects(SymbolFlags::Type | SymbolFlags::TypeImport)
+    && !flags.intersects(SymbolFlags::Value | SymbolFlags::Import)
+}
+
+/// Finds names of enums with multiple declarations, i.e. `enum A { X }` and `enum A { Y = 1 }`.
+fn find_merged_enums(program: &Program) -> HashSet<String> {
+  let mut names = HashSet::new();
+  let mut merged = HashSet::new();
+
+  for stmt in &program.body {
+    let decl = match stmt {
+      Statement::TSEnumDeclaration(decl) => decl,
+      Statement::ExportNamedDeclaration(export) => match &export.declaration {
+        Some(Declaration::TSEnumDeclaration(decl)) => decl,
+        _ => continue,
+      },
+      _ => continue,
+    };
+
+    if !names.insert(decl.id.name.as_str()) {
+      merged.insert(decl.id.name.to_string());
+    }
+  }
+
+  merged
+}
+
+/// Checks if `export default name` can be an alias of the binding. Globals & reassigned bindings
+/// need a new binding, an alias would export a live binding instead of the value at the time of
+/// the export.
+fn is_constant_root_binding(name: &str, ctx: &TraverseCtx) -> bool {
+  let Some(symbol_id) = ctx.scopes().get_root_binding(name) else {
+    return false;
+  };
+
+  ctx
+    .symbols()
+    .get_resolved_reference_ids(symbol_id)
+    .iter()
+    .all(|reference_id| !ctx.symbols().get_reference(*reference_id).is_write())
+}
+
+/// Formats a number the same way as `Number.prototype.toString()` does, see `Number::toString`
+/// in the ECMAScript specification. Rust's exponent format provides the shortest digits that
+/// round trip, only their placement differs.
+pub(crate) fn number_to_js_string(value: f64) -> String {
+  if value.is_nan() {
+    return "NaN".to_string();
+  }
+  if value == 0.0 {
+    return "0".to_string();
+  }
+  if value < 0.0 {
+    return format!("-{}", number_to_js_string(-value));
+  }
+  if value.is_infinite() {
+    return "Infinity".to_string();
+  }
+
+  let formatted = format!("{value:e}");
+  let (mantissa, exponent) = formatted
+    .split_once('e')
+    .expect("the exponent format has an exponent");
+  let digits = mantissa.replace('.', "");
+  let k = digits.len() as i32;
+  let n = exponent.parse::<i32>().expect("the exponent is an integer") + 1;
+
+  if k <= n && n <= 21 {
+    format!("{digits}{}", "0".repeat((n - k) as usize))
+  } else if 0 < n && n <= 21 {
+    format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+  } else if -6 < n && n <= 0 {
+    format!("0.{}{digits}", "0".repeat(-n as usize))
+  } else {
+    let sign = if n - 1 < 0 { '-' } else { '+' };
+    let exponent = (n - 1).abs();
+
+    match digits.split_at(1) {
+      (first, "") => format!("{first}e{sign}{exponent}"),
+      (first, rest) => format!("{first}.{rest}e{sign}{exponent}"),
+    }
+  }
+}
+
+fn evaluate_enum_initializer(expression: &Expression) -> Option<f64> {
+  match expression.get_inner_expression() {
+    Expression::NumericLiteral(lit) => Some(lit.value),
+    Expression::UnaryExpression(unary) if unary.operator == UnaryOperator::UnaryNegation => {
+      match unary.argument.get_inner_expression() {
+        Expression::NumericLiteral(lit) => Some(-lit.value),
+        _ => None,
+      }
+    }
+    _ => None,
+  }
+}
+
 fn create_export_specifiers<'a>(
   allocator: &'a Allocator,
   ast_builder: AstBuilder<'a>,
-  id: &Option<BindingIdentifier<'a>>,
+  id: &BindingIdentifier<'a>,
 ) -> ArenaVec<'a, ExportSpecifier<'a>> {
   let mut specifiers: ArenaVec<ExportSpecifier<'a>> = ArenaVec::with_capacity_in(1, allocator);
 
-  match id {
-    // TODO: use .span() properly
-    Some(id) => {
-      let local = ast_builder.module_export_name_identifier_name(SPAN, &id.name);
-      let exported = ast_builder.module_export_name_identifier_name(SPAN, &id.name);
+  let local = ast_builder.module_export_name_identifier_reference(id.span, &id.name);
+  let exported = ast_builder.module_export_name_identifier_name(id.span, &id.name);
 
-      let specifier =
-        ast_builder.export_specifier(SPAN, local, exported, ImportOrExportKind::Value);
+  let specifier = ast_builder.export_specifier(id.span, local, exported, ImportOrExportKind::Value);
 
-      specifiers.push(specifier);
-    }
-
-    _ => {
-      panic!("panic: not implemented");
-    }
-  }
+  specifiers.push(specifier);
 
   specifiers
 }
 
 fn create_named_export_stmt<'a>(
   ast_builder: AstBuilder<'a>,
+  span: Span,
   specifiers: ArenaVec<'a, ExportSpecifier<'a>>,
 ) -> Statement<'a> {
   let export_named = ast_builder.module_declaration_export_named_declaration(
-    SPAN,
+    span,
     None,
     specifiers,
     None,
@@ -54,13 +208,33 @@ fn create_named_export_stmt<'a>(
   export_named.into()
 }
 
+fn create_default_export_stmt<'a>(
+  allocator: &'a Allocator,
+  ast_builder: AstBuilder<'a>,
+  span: Span,
+  local_name: &str,
+) -> Statement<'a> {
+  let mut specifiers: ArenaVec<ExportSpecifier<'a>> = ArenaVec::with_capacity_in(1, allocator);
+
+  let specifier = ast_builder.export_specifier(
+    span,
+    ast_builder.module_export_name_identifier_reference(span, local_name),
+    ast_builder.module_export_name_identifier_name(SPAN, "default"),
+    ImportOrExportKind::Value,
+  );
+  specifiers.push(specifier);
+
+  create_named_export_stmt(ast_builder, span, specifiers)
+}
+
 fn create_named_import_stmt<'a>(
   ast_builder: AstBuilder<'a>,
+  span: Span,
   specifiers: Option<ArenaVec<'a, ImportDeclarationSpecifier<'a>>>,
   source: StringLiteral<'a>,
 ) -> Statement<'a> {
   let import_named = ast_builder.module_declaration_import_declaration(
-    SPAN,
+    span,
     specifiers,
     source,
     None,
@@ -75,6 +249,12 @@ pub(crate) struct ExportReplacer<'a> {
   pub ast: AstBuilder<'a>,
 
   pub filename: &'a str,
+
+  /// Statements that can't be expanded, they are kept in the output untouched.
+  pub errors: Vec<OxcDiagnostic>,
+
+  /// Names of enums that are declared more than once, TypeScript merges their members.
+  merged_enums: HashSet<String>,
 }
 
 impl<'a> ExportReplacer<'a> {
@@ -83,6 +263,8 @@ impl<'a> ExportReplacer<'a> {
       allocator,
       ast,
       filename,
+      errors: Vec::new(),
+      merged_enums: HashSet::new(),
     }
   }
 
@@ -93,166 +275,243 @@ impl<'a> ExportReplacer<'a> {
   {
     walk_program(self, program, ctx);
   }
-}
 
-impl<'a> Traverse<'a> for ExportReplacer<'a> {
-  fn exit_program(&mut self, program: &mut Program<'a>, ctx: &mut TraverseCtx<'a>) {
-    let mut new_body: ArenaVec<'a, Statement<'a>> = ArenaVec::new_in(self.allocator);
+  fn create_numeric_expression(&self, value: f64) -> Expression<'a> {
+    let raw = self.ast.str(&number_to_js_string(value.abs()));
+    let literal = self
+      .ast
+      .expression_numeric_literal(SPAN, value.abs(), raw, NumberBase::Decimal);
 
-    for stmt in &program.body {
-      match stmt {
-        Statement::ImportDeclaration(decl) => match &decl.specifiers {
-          Some(specifiers) => {
-            for specifier in specifiers {
-              match specifier {
-                ImportDeclarationSpecifier::ImportSpecifier(import_specifier) => {
-                  let mut import_specifiers: ArenaVec<ImportDeclarationSpecifier<'a>> =
-                    ArenaVec::with_capacity_in(1, self.allocator);
-                  let new_specifier = self.ast.import_declaration_specifier_import_specifier(
-                    SPAN,
-                    self
-                      .ast
-                      .module_export_name_identifier_name(SPAN, &import_specifier.local.name),
-                    self
-                      .ast
-                      .binding_identifier(SPAN, &import_specifier.imported.name()),
-                    ImportOrExportKind::Value,
-                  );
-                  import_specifiers.push(new_specifier);
-
-                  let source = decl.source.clone();
-                  let import_stmt =
-                    create_named_import_stmt(self.ast, Some(import_specifiers), source);
-                  new_body.push(import_stmt);
-                }
-
-                ImportDeclarationSpecifier::ImportDefaultSpecifier(import_default_specifier) => {
-                  let mut import_specifiers: ArenaVec<ImportDeclarationSpecifier<'a>> =
-                    ArenaVec::with_capacity_in(1, self.allocator);
-                  let new_specifier = self.ast.import_declaration_specifier_import_specifier(
-                    SPAN,
-                    self.ast.module_export_name_identifier_name(SPAN, "default"),
-                    self
-                      .ast
-                      .binding_identifier(SPAN, &import_default_specifier.local.name),
-                    ImportOrExportKind::Value,
-                  );
-                  import_specifiers.push(new_specifier);
-
-                  let source = decl.source.clone();
-                  let import_stmt =
-                    create_named_import_stmt(self.ast, Some(import_specifiers), source);
-                  new_body.push(import_stmt);
-                }
-
-                ImportDeclarationSpecifier::ImportNamespaceSpecifier(
-                  import_namespace_specifier,
-                ) => {
-                  new_body.push(stmt.clone_in(self.allocator));
-                }
-
-                _ => {
-                  panic!("panic: not implemented, file {}", self.filename);
-                }
-              }
-            }
-          }
-          None => {}
-        },
+    if value.is_sign_negative() {
+      return self
+        .ast
+        .expression_unary(SPAN, UnaryOperator::UnaryNegation, literal);
+    }
 
-        Statement::ExportDefaultDeclaration(decl) => match &decl.declaration {
-          ExportDefaultDeclarationKind::FunctionDeclaration(func) => {
-            let mut export_specifiers: ArenaVec<ExportSpecifier<'a>> =
-              ArenaVec::with_capacity_in(1, self.allocator);
-            let mut new_func = func.clone_in(self.allocator);
-
-            match &func.id {
-              Some(id) => {
-                let new_specifier = self.ast.export_specifier(
-                  SPAN,
-                  self.ast.module_export_name_identifier_name(SPAN, &id.name),
-                  self.ast.module_export_name_identifier_name(SPAN, "default"),
-                  ImportOrExportKind::Value,
-                );
+    literal
+  }
 
-                export_specifiers.push(new_specifier);
-              }
-              None => {
-                let symbol_id = ctx.scoping.generate_uid_in_root_scope(
-                  "export",
-                  oxc_semantic::SymbolFlags::FunctionScopedVariable,
-                );
-                let variable_name = ctx.ast.atom(&ctx.symbols().names[symbol_id]);
-
-                let new_specifier = self.ast.export_specifier(
-                  SPAN,
-                  self
-                    .ast
-                    .module_export_name_identifier_name(SPAN, variable_name.clone()),
-                  self.ast.module_export_name_identifier_name(SPAN, "default"),
-                  ImportOrExportKind::Value,
-                );
+  /// Lowers an enum to a plain object, the same as TypeScript does it (including reverse mappings
+  /// for numeric members), so it can be tracked as a regular binding:
+  ///
+  /// ```ts
+  /// enum Direction { Up, Down = "down" }
+  /// // ⬇️
+  /// const Direction = { Up: 0, Down: "down", "0": "Up" };
+  /// ```
+  fn lower_enum(&self, decl: &TSEnumDeclaration<'a>) -> Result<Statement<'a>, OxcDiagnostic> {
+    if self.merged_enums.contains(decl.id.name.as_str()) {
+      return Err(unsupported_syntax(
+        "enum that is declared more than once",
+        decl.id.span,
+      ));
+    }
 
-                export_specifiers.push(new_specifier);
-                new_func.id = Some(self.ast.binding_identifier(SPAN, variable_name.clone()));
-              }
+    let mut properties = self.ast.vec();
+    let mut reverse_properties = self.ast.vec();
+
+    let mut next_value: Option<f64> = Some(0.0);
+
+    for member in &decl.members {
+      let (key, name) = match &member.id {
+        TSEnumMemberName::StaticIdentifier(ident) => (
+          self
+            .ast
+            .property_key_identifier_name(ident.span, ident.name.clone()),
+          ident.name.clone(),
+        ),
+        TSEnumMemberName::StaticStringLiteral(lit) => (
+          PropertyKey::from(
+            self
+              .ast
+              .expression_string_literal(lit.span, lit.value.clone()),
+          ),
+          lit.value.clone(),
+        ),
+        id => {
+          return Err(unsupported_syntax("computed enum member name", id.span()));
+        }
+      };
+
+      let (value, numeric_value) = match &member.initializer {
+        None => {
+          let Some(value) = next_value else {
+            return Err(unsupported_syntax(
+              "enum member without an initializer after a non-numeric member",
+              member.span,
+            ));
+          };
+
+          (self.create_numeric_expression(value), Some(value))
+        }
+        Some(initializer) => match initializer.get_inner_expression() {
+          Expression::StringLiteral(_) => (initializer.clone_in(self.allocator), None),
+          _ => match evaluate_enum_initializer(initializer) {
+            Some(value) => (initializer.clone_in(self.allocator), Some(value)),
+            None => {
+              return Err(unsupported_syntax(
+                "enum member initializer that is not a literal",
+                initializer.span(),
+              ));
             }
+          },
+        },
+      };
+
+      properties.push(self.ast.object_property_kind_object_property(
+        member.span,
+        PropertyKind::Init,
+        key,
+        value,
+        None,
+        false,
+        false,
+        false,
+      ));
+
+      if let Some(numeric_value) = numeric_value {
+        let reverse_key = self.ast.str(&number_to_js_string(numeric_value));
+
+        reverse_properties.push(self.ast.object_property_kind_object_property(
+          SPAN,
+          PropertyKind::Init,
+          PropertyKey::from(self.ast.expression_string_literal(SPAN, reverse_key)),
+          self.ast.expression_string_literal(SPAN, name),
+          None,
+          false,
+          false,
+          false,
+        ));
+      }
 
-            let export_stmt = create_named_export_stmt(self.ast, export_specifiers);
-            let new_decl = ctx.ast.declaration_from_function(new_func);
+      next_value = numeric_value.map(|value| value + 1.0);
+    }
 
-            new_body.push(new_decl.into());
-            new_body.push(export_stmt);
-          }
+    properties.extend(reverse_properties);
+
+    let mut var_declarations: ArenaVec<VariableDeclarator<'a>> =
+      ArenaVec::with_capacity_in(1, self.allocator);
+
+    let binding_pattern = self.ast.binding_pattern(
+      self.ast.binding_pattern_kind_from_binding_identifier(
+        self
+          .ast
+          .binding_identifier(decl.id.span, decl.id.name.clone()),
+      ),
+      Option::<TSTypeAnnotation>::None,
+      false,
+    );
+
+    let variable_declarator = self.ast.variable_declarator(
+      SPAN,
+      VariableDeclarationKind::Const,
+      binding_pattern,
+      Some(self.ast.expression_object(SPAN, properties, None)),
+      false,
+    );
+
+    var_declarations.push(variable_declarator);
+
+    let var_declaration = self.ast.variable_declaration(
+      decl.span,
+      VariableDeclarationKind::Const,
+      var_declarations,
+      false,
+    );
+
+    Ok(self.ast.declaration_from_variable(var_declaration).into())
+  }
 
-          ExportDefaultDeclarationKind::ClassDeclaration(class) => {
-            let mut export_specifiers: ArenaVec<ExportSpecifier<'a>> =
-              ArenaVec::with_capacity_in(1, self.allocator);
-            let mut new_class = class.clone_in(self.allocator);
-
-            match &class.id {
-              Some(id) => {
-                let new_specifier = self.ast.export_specifier(
-                  SPAN,
-                  self.ast.module_export_name_identifier_name(SPAN, &id.name),
-                  self.ast.module_export_name_identifier_name(SPAN, "default"),
-                  ImportOrExportKind::Value,
-                );
+  fn expand_import_declaration(
+    &mut self,
+    stmt: &Statement<'a>,
+    decl: &ImportDeclaration<'a>,
+    new_body: &mut ArenaVec<'a, Statement<'a>>,
+  ) -> Result<(), OxcDiagnostic> {
+    // Bare imports are kept as is, the shaker decides if the module has side effects
+    let Some(specifiers) = decl
+      .specifiers
+      .as_ref()
+      .filter(|specifiers| !specifiers.is_empty())
+    else {
+      new_body.push(stmt.clone_in(self.allocator));
+      return Ok(());
+    };
 
-                export_specifiers.push(new_specifier);
-              }
-              None => {
-                let symbol_id = ctx.scoping.generate_uid_in_root_scope(
-                  "export",
-                  oxc_semantic::SymbolFlags::FunctionScopedVariable,
-                );
-                let variable_name = ctx.ast.atom(&ctx.symbols().names[symbol_id]);
-
-                let new_specifier = self.ast.export_specifier(
-                  SPAN,
-                  self
-                    .ast
-                    .module_export_name_identifier_name(SPAN, variable_name.clone()),
-                  self.ast.module_export_name_identifier_name(SPAN, "default"),
-                  ImportOrExportKind::Value,
-                );
+    for specifier in specifiers {
+      match specifier {
+        ImportDeclarationSpecifier::ImportSpecifier(import_specifier)
+          if import_specifier.import_kind.is_type() => {}
+
+        ImportDeclarationSpecifier::ImportSpecifier(import_specifier) => {
+          let mut import_specifiers: ArenaVec<ImportDeclarationSpecifier<'a>> =
+            ArenaVec::with_capacity_in(1, self.allocator);
+          let new_specifier = self.ast.import_declaration_specifier_import_specifier(
+            import_specifier.span,
+            import_specifier.imported.clone_in(self.allocator),
+            import_specifier.local.clone_in(self.allocator),
+            ImportOrExportKind::Value,
+          );
+          import_specifiers.push(new_specifier);
+
+          let source = decl.source.clone();
+          let import_stmt =
+            create_named_import_stmt(self.ast, decl.span, Some(import_specifiers), source);
+          new_body.push(import_stmt);
+        }
 
-                export_specifiers.push(new_specifier);
-                new_class.id = Some(self.ast.binding_identifier(SPAN, variable_name.clone()));
-              }
-            }
+        ImportDeclarationSpecifier::ImportDefaultSpecifier(import_default_specifier) => {
+          let mut import_specifiers: ArenaVec<ImportDeclarationSpecifier<'a>> =
+            ArenaVec::with_capacity_in(1, self.allocator);
+          let new_specifier = self.ast.import_declaration_specifier_import_specifier(
+            import_default_specifier.span,
+            self.ast.module_export_name_identifier_name(SPAN, "default"),
+            import_default_specifier.local.clone_in(self.allocator),
+            ImportOrExportKind::Value,
+          );
+          import_specifiers.push(new_specifier);
+
+          let source = decl.source.clone();
+          let import_stmt =
+            create_named_import_stmt(self.ast, decl.span, Some(import_specifiers), source);
+          new_body.push(import_stmt);
+        }
 
-            let export_stmt = create_named_export_stmt(self.ast, export_specifiers);
-            let new_decl = ctx.ast.declaration_from_class(new_class);
+        ImportDeclarationSpecifier::ImportNamespaceSpecifier(_) => {
+          new_body.push(stmt.clone_in(self.allocator));
+        }
+      }
+    }
 
-            new_body.push(new_decl.into());
-            new_body.push(export_stmt);
-          }
+    Ok(())
+  }
 
-          ExportDefaultDeclarationKind::ObjectExpression(obj) => {
-            let mut export_specifiers: ArenaVec<ExportSpecifier<'a>> =
-              ArenaVec::with_capacity_in(1, self.allocator);
+  fn expand_export_default_declaration(
+    &mut self,
+    decl: &ExportDefaultDeclaration<'a>,
+    new_body: &mut ArenaVec<'a, Statement<'a>>,
+    ctx: &mut TraverseCtx<'a>,
+  ) -> Result<(), OxcDiagnostic> {
+    match &decl.declaration {
+      ExportDefaultDeclarationKind::FunctionDeclaration(func) => {
+        let mut export_specifiers: ArenaVec<ExportSpecifier<'a>> =
+          ArenaVec::with_capacity_in(1, self.allocator);
+        let mut new_func = func.clone_in(self.allocator);
+
+        match &func.id {
+          Some(id) => {
+            let new_specifier = self.ast.export_specifier(
+              id.span,
+              self
+                .ast
+                .module_export_name_identifier_reference(id.span, &id.name),
+              self.ast.module_export_name_identifier_name(SPAN, "default"),
+              ImportOrExportKind::Value,
+            );
 
+            export_specifiers.push(new_specifier);
+          }
+          None => {
             let symbol_id = ctx.scoping.generate_uid_in_root_scope(
               "export",
               oxc_semantic::SymbolFlags::FunctionScopedVariable,
@@ -263,191 +522,378 @@ impl<'a> Traverse<'a> for ExportReplacer<'a> {
               SPAN,
               self
                 .ast
-                .module_export_name_identifier_name(SPAN, variable_name.clone()),
+                .module_export_name_identifier_reference(SPAN, variable_name.clone()),
               self.ast.module_export_name_identifier_name(SPAN, "default"),
               ImportOrExportKind::Value,
             );
 
             export_specifiers.push(new_specifier);
+            new_func.id = Some(self.ast.binding_identifier(SPAN, variable_name.clone()));
+          }
+        }
 
-            let export_stmt = create_named_export_stmt(self.ast, export_specifiers);
+        let export_stmt = create_named_export_stmt(self.ast, decl.span, export_specifiers);
+        let new_decl = ctx.ast.declaration_from_function(new_func);
 
-            let mut var_declarations: ArenaVec<VariableDeclarator<'a>> =
-              ArenaVec::with_capacity_in(1, self.allocator);
+        new_body.push(new_decl.into());
+        new_body.push(export_stmt);
+      }
 
-            let binding_pattern = ctx.ast.binding_pattern(
-              ctx.ast.binding_pattern_kind_from_binding_identifier(
-                ctx.ast.binding_identifier(SPAN, variable_name.clone()),
-              ),
-              Option::<TSTypeAnnotation>::None,
-              false,
-            );
+      ExportDefaultDeclarationKind::ClassDeclaration(class) => {
+        let mut export_specifiers: ArenaVec<ExportSpecifier<'a>> =
+          ArenaVec::with_capacity_in(1, self.allocator);
+        let mut new_class = class.clone_in(self.allocator);
 
-            let variable_declarator = ctx.ast.variable_declarator(
-              SPAN,
-              VariableDeclarationKind::Const,
-              binding_pattern,
-              Some(ctx.ast.expression_from_object(obj.clone_in(self.allocator))),
-              false,
+        match &class.id {
+          Some(id) => {
+            let new_specifier = self.ast.export_specifier(
+              id.span,
+              self
+                .ast
+                .module_export_name_identifier_reference(id.span, &id.name),
+              self.ast.module_export_name_identifier_name(SPAN, "default"),
+              ImportOrExportKind::Value,
             );
 
-            var_declarations.push(variable_declarator);
+            export_specifiers.push(new_specifier);
+          }
+          None => {
+            let symbol_id = ctx.scoping.generate_uid_in_root_scope(
+              "export",
+              oxc_semantic::SymbolFlags::FunctionScopedVariable,
+            );
+            let variable_name = ctx.ast.atom(&ctx.symbols().names[symbol_id]);
 
-            let var_declaration = ctx.ast.variable_declaration(
+            let new_specifier = self.ast.export_specifier(
               SPAN,
-              VariableDeclarationKind::Const,
-              var_declarations,
-              false,
+              self
+                .ast
+                .module_export_name_identifier_reference(SPAN, variable_name.clone()),
+              self.ast.module_export_name_identifier_name(SPAN, "default"),
+              ImportOrExportKind::Value,
             );
 
-            let new_decl = ctx.ast.declaration_from_variable(var_declaration);
-
-            new_body.push(new_decl.into());
-            new_body.push(export_stmt);
+            export_specifiers.push(new_specifier);
+            new_class.id = Some(self.ast.binding_identifier(SPAN, variable_name.clone()));
           }
+        }
 
-          _ => {
-            panic!("panic: not implemented");
-          }
-        },
+        let export_stmt = create_named_export_stmt(self.ast, decl.span, export_specifiers);
+        let new_decl = ctx.ast.declaration_from_class(new_class);
+
+        new_body.push(new_decl.into());
+        new_body.push(export_stmt);
+      }
 
-        Statement::ExportNamedDeclaration(decl) => match &decl.declaration {
-          Some(Declaration::FunctionDeclaration(func)) => {
-            let specifiers = create_export_specifiers(self.allocator, self.ast, &func.id);
-            let export_stmt = create_named_export_stmt(self.ast, specifiers);
+      // Interfaces don't exist at runtime
+      ExportDefaultDeclarationKind::TSInterfaceDeclaration(_) => {}
 
-            let new_decl = ctx
-              .ast
-              .declaration_from_function(func.clone_in(self.allocator));
+      kind => {
+        let Some(expression) = kind.as_expression() else {
+          return Err(unsupported_syntax(
+            "`export default` of this kind of expression",
+            kind.span(),
+          ));
+        };
+
+        // `export default foo` only needs an alias, there is no reason to create a new binding
+        if let Expression::Identifier(ident) = expression.get_inner_expression() {
+          if is_constant_root_binding(&ident.name, ctx) {
+            let export_stmt =
+              create_default_export_stmt(self.allocator, self.ast, decl.span, &ident.name);
 
-            new_body.push(new_decl.into());
             new_body.push(export_stmt);
+
+            return Ok(());
           }
+        }
 
-          Some(Declaration::VariableDeclaration(var)) => {
-            for decl in &var.declarations {
-              match &decl.kind {
-                VariableDeclarationKind::Var
-                | VariableDeclarationKind::Const
-                | VariableDeclarationKind::Let => {
-                  // let mut specifiers = ArenaVec::with_capacity_in(1, self.allocator);
-
-                  match &decl.id.kind {
-                    BindingPatternKind::BindingIdentifier(id) => {
-                      let specifiers = create_export_specifiers(
-                        self.allocator,
-                        self.ast,
-                        &Some(id.deref().clone()),
-                      );
-                      let export_stmt = create_named_export_stmt(self.ast, specifiers);
-
-                      let new_decl = ctx
-                        .ast
-                        .declaration_from_variable(var.clone_in(self.allocator));
-
-                      new_body.push(new_decl.into());
-                      new_body.push(export_stmt);
-                    }
-
-                    BindingPatternKind::ObjectPattern(obj) => {
-                      let new_decl = ctx
-                        .ast
-                        .declaration_from_variable(var.clone_in(self.allocator));
-
-                      new_body.push(new_decl.into());
-
-                      for property in &obj.properties {
-                        let value = property.value.get_binding_identifier();
-
-                        match value {
-                          Some(value) => {
-                            let specifiers = create_export_specifiers(
-                              self.allocator,
-                              self.ast,
-                              &Some(value.clone_in(self.allocator)),
-                            );
-                            let export_stmt = create_named_export_stmt(self.ast, specifiers);
-
-                            new_body.push(export_stmt);
-                          }
-                          None => {
-                            panic!(
-                              "panic: not implemented for `property.value`, file {}",
-                              self.filename
-                            );
-                          }
-                        }
-                      }
-                    }
-
-                    _ => {
-                      panic!(
-                        "panic: not implemented for `decl.id`, file {}",
-                        self.filename
-                      );
-                    }
-                  }
-                }
-
-                _ => {
-                  panic!("panic: not implemented for `var.declarations`");
-                }
-              }
+        let symbol_id = ctx
+          .scoping
+          .generate_uid_in_root_scope("export", oxc_semantic::SymbolFlags::FunctionScopedVariable);
+        let variable_name = ctx.ast.atom(&ctx.symbols().names[symbol_id]);
+
+        let mut var_declarations: ArenaVec<VariableDeclarator<'a>> =
+          ArenaVec::with_capacity_in(1, self.allocator);
+
+        let binding_pattern = ctx.ast.binding_pattern(
+          ctx.ast.binding_pattern_kind_from_binding_identifier(
+            ctx.ast.binding_identifier(SPAN, variable_name.clone()),
+          ),
+          Option::<TSTypeAnnotation>::None,
+          false,
+        );
+
+        let variable_declarator = ctx.ast.variable_declarator(
+          expression.span(),
+          VariableDeclarationKind::Const,
+          binding_pattern,
+          Some(expression.clone_in(self.allocator)),
+          false,
+        );
+
+        var_declarations.push(variable_declarator);
+
+        let var_declaration = ctx.ast.variable_declaration(
+          decl.span,
+          VariableDeclarationKind::Const,
+          var_declarations,
+          false,
+        );
+
+        let new_decl = ctx.ast.declaration_from_variable(var_declaration);
+        let export_stmt =
+          create_default_export_stmt(self.allocator, self.ast, decl.span, &variable_name);
+
+        new_body.push(new_decl.into());
+        new_body.push(export_stmt);
+      }
+    }
+
+    Ok(())
+  }
+
+  fn expand_export_named_declaration(
+    &mut self,
+    decl: &ExportNamedDeclaration<'a>,
+    new_body: &mut ArenaVec<'a, Statement<'a>>,
+    ctx: &mut TraverseCtx<'a>,
+  ) -> Result<(), OxcDiagnostic> {
+    match &decl.declaration {
+      Some(Declaration::FunctionDeclaration(func)) => {
+        let Some(id) = &func.id else {
+          return Err(unsupported_syntax(
+            "exported function without a name",
+            func.span,
+          ));
+        };
+
+        let specifiers = create_export_specifiers(self.allocator, self.ast, id);
+        let export_stmt = create_named_export_stmt(self.ast, decl.span, specifiers);
+
+        let new_decl = ctx
+          .ast
+          .declaration_from_function(func.clone_in(self.allocator));
+
+        new_body.push(new_decl.into());
+        new_body.push(export_stmt);
+      }
 
-              //               match &decl.id {
-              // // VariableDeclarator::
-              //               }
+      Some(Declaration::ClassDeclaration(class)) => {
+        let Some(id) = &class.id else {
+          return Err(unsupported_syntax(
+            "exported class without a name",
+            class.span,
+          ));
+        };
+
+        let specifiers = create_export_specifiers(self.allocator, self.ast, id);
+        let export_stmt = create_named_export_stmt(self.ast, decl.span, specifiers);
+
+        let new_decl = ctx
+          .ast
+          .declaration_from_class(class.clone_in(self.allocator));
+
+        new_body.push(new_decl.into());
+        new_body.push(export_stmt);
+      }
+
+      Some(Declaration::TSEnumDeclaration(enum_decl)) => {
+        let specifiers = create_export_specifiers(self.allocator, self.ast, &enum_decl.id);
+        let export_stmt = create_named_export_stmt(self.ast, decl.span, specifiers);
+
+        new_body.push(self.lower_enum(enum_decl)?);
+        new_body.push(export_stmt);
+      }
+
+      Some(Declaration::TSModuleDeclaration(module)) => {
+        return Err(unsupported_syntax(
+          "namespace that contains runtime values",
+          module.span,
+        ));
+      }
+
+      Some(Declaration::VariableDeclaration(var)) => {
+        // `export const a = 1, b = 2` is split to a declaration per declarator, so every binding
+        // is declared once
+        for decl in &var.declarations {
+          let mut declarations: ArenaVec<VariableDeclarator<'a>> =
+            ArenaVec::with_capacity_in(1, self.allocator);
+          declarations.push(decl.clone_in(self.allocator));
+
+          let new_decl = ctx
+            .ast
+            .declaration_from_variable(ctx.ast.variable_declaration(
+              decl.span,
+              var.kind,
+              declarations,
+              var.declare,
+            ));
+          new_body.push(new_decl.into());
+
+          match &decl.id.kind {
+            BindingPatternKind::BindingIdentifier(id) => {
+              let specifiers = create_export_specifiers(self.allocator, self.ast, id.deref());
+              let export_stmt = create_named_export_stmt(self.ast, decl.span, specifiers);
+
+              new_body.push(export_stmt);
             }
-          }
 
-          None => {
-            for specifier in &decl.specifiers {
-              let mut export_specifiers: ArenaVec<ExportSpecifier<'a>> =
-                ArenaVec::with_capacity_in(1, self.allocator);
-              export_specifiers.push(specifier.clone_in(self.allocator));
-
-              let mut source: Option<StringLiteral<'a>> = None;
-
-              if decl.source.is_some() {
-                source = decl.source.clone();
-
-                let mut import_specifiers: ArenaVec<ImportDeclarationSpecifier<'a>> =
-                  ArenaVec::with_capacity_in(1, self.allocator);
-
-                match &specifier.exported {
-                  ModuleExportName::IdentifierName(_) => {
-                    let import_specifier = self.ast.import_declaration_specifier_import_specifier(
-                      SPAN,
-                      specifier.local.clone(),
-                      self
-                        .ast
-                        .binding_identifier(SPAN, &specifier.exported.name()),
-                      ImportOrExportKind::Value,
-                    );
-
-                    import_specifiers.push(import_specifier);
-                  }
-                  _ => {
-                    panic!("panic: not implemented");
-                  }
-                }
-
-                let import_stmt =
-                  create_named_import_stmt(self.ast, Some(import_specifiers), source.unwrap());
-                new_body.push(import_stmt);
+            BindingPatternKind::ObjectPattern(obj) => {
+              if let Some(rest) = &obj.rest {
+                return Err(unsupported_syntax(
+                  "rest element in an exported object pattern",
+                  rest.span,
+                ));
               }
 
-              let export_stmt = create_named_export_stmt(self.ast, export_specifiers);
+              for property in &obj.properties {
+                let Some(value) = property.value.get_binding_identifier() else {
+                  return Err(unsupported_syntax(
+                    "nested pattern in an exported object pattern",
+                    property.span,
+                  ));
+                };
 
-              new_body.push(export_stmt);
+                let specifiers = create_export_specifiers(self.allocator, self.ast, value);
+                let export_stmt = create_named_export_stmt(self.ast, decl.span, specifiers);
+
+                new_body.push(export_stmt);
+              }
+            }
+
+            BindingPatternKind::ArrayPattern(arr) => {
+              return Err(unsupported_syntax("exported array pattern", arr.span));
+            }
+
+            BindingPatternKind::AssignmentPattern(assign) => {
+              return Err(unsupported_syntax(
+                "exported assignment pattern",
+                assign.span,
+              ));
             }
           }
+        }
+      }
 
-          _ => {
-            panic!("panic: not implemented, file {}", self.filename);
+      None => {
+        for specifier in &decl.specifiers {
+          if specifier.export_kind.is_type()
+            || (decl.source.is_none() && is_type_only_binding(&specifier.local.name(), ctx))
+          {
+            continue;
           }
-        },
+
+          let mut export_specifiers: ArenaVec<ExportSpecifier<'a>> =
+            ArenaVec::with_capacity_in(1, self.allocator);
+
+          if let Some(source) = &decl.source {
+            let mut import_specifiers: ArenaVec<ImportDeclarationSpecifier<'a>> =
+              ArenaVec::with_capacity_in(1, self.allocator);
+
+            match &specifier.exported {
+              ModuleExportName::IdentifierName(exported) => {
+                // `export { foo as bar } from "./foo"` becomes
+                // `import { foo as bar } from "./foo"; export { bar };`
+                let import_specifier = self.ast.import_declaration_specifier_import_specifier(
+                  specifier.span,
+                  specifier.local.clone_in(self.allocator),
+                  self.ast.binding_identifier(exported.span, &exported.name),
+                  ImportOrExportKind::Value,
+                );
+
+                import_specifiers.push(import_specifier);
+                export_specifiers.push(
+                  self.ast.export_specifier(
+                    specifier.span,
+                    self
+                      .ast
+                      .module_export_name_identifier_reference(exported.span, &exported.name),
+                    specifier.exported.clone_in(self.allocator),
+                    ImportOrExportKind::Value,
+                  ),
+                );
+              }
+              exported => {
+                return Err(unsupported_syntax(
+                  "re-export with a string literal name",
+                  exported.span(),
+                ));
+              }
+            }
+
+            let import_stmt = create_named_import_stmt(
+              self.ast,
+              decl.span,
+              Some(import_specifiers),
+              source.clone(),
+            );
+            new_body.push(import_stmt);
+          } else {
+            export_specifiers.push(specifier.clone_in(self.allocator));
+          }
+
+          let export_stmt = create_named_export_stmt(self.ast, specifier.span, export_specifiers);
+
+          new_body.push(export_stmt);
+        }
+      }
+
+      Some(declaration) => {
+        return Err(unsupported_syntax(
+          "exported declaration of this kind",
+          declaration.span(),
+        ));
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl<'a> Traverse<'a> for ExportReplacer<'a> {
+  fn exit_program(&mut self, program: &mut Program<'a>, ctx: &mut TraverseCtx<'a>) {
+    self.merged_enums = find_merged_enums(program);
+
+    let mut new_body: ArenaVec<'a, Statement<'a>> = ArenaVec::new_in(self.allocator);
+
+    for stmt in &program.body {
+      // Each statement is expanded into a separate list, so a statement that fails midway doesn't
+      // leave partially generated nodes behind.
+      let mut stmt_body: ArenaVec<'a, Statement<'a>> = ArenaVec::new_in(self.allocator);
+
+      // Types, interfaces & ambient declarations have no runtime semantics, they are erased
+      if is_type_only_statement(stmt) {
+        continue;
+      }
+
+      let result = match stmt {
+        Statement::TSEnumDeclaration(decl) => self.lower_enum(decl).map(|enum_stmt| {
+          stmt_body.push(enum_stmt);
+        }),
+        Statement::TSModuleDeclaration(module) => Err(unsupported_syntax(
+          "namespace that contains runtime values",
+          module.span,
+        )),
+        Statement::ImportDeclaration(decl) => {
+          self.expand_import_declaration(stmt, decl, &mut stmt_body)
+        }
+        Statement::ExportDefaultDeclaration(decl) => {
+          self.expand_export_default_declaration(decl, &mut stmt_body, ctx)
+        }
+        Statement::ExportNamedDeclaration(decl) => {
+          self.expand_export_named_declaration(decl, &mut stmt_body, ctx)
+        }
         _ => {
+          stmt_body.push(stmt.clone_in(self.allocator));
+          Ok(())
+        }
+      };
+
+      match result {
+        Ok(()) => new_body.extend(stmt_body),
+        Err(diagnostic) => {
+          self.errors.push(diagnostic);
           new_body.push(stmt.clone_in(self.allocator));
         }
       }
@@ -468,9 +914,7 @@ mod tests {
   use crate::context::TransformContext;
   use crate::export_expand::ExportReplacer;
 
-  pub fn assert_fixture(input: &str, expected: &str) {
-    let source_file = "source.js";
-
+  fn run_fixture(source_file: &str, input: &str) -> (String, usize) {
     let allocator = Allocator::default();
     let ctx = TransformContext::new(&allocator, &source_file, &input);
 
@@ -482,14 +926,58 @@ mod tests {
 
     let mut traverse_ctx = TraverseCtx::new(scopes, symbols, ctx.allocator);
 
-    ExportReplacer::new(ctx.allocator, ast_builder, "source.js")
-      .build(&mut ctx.program_mut(), &mut traverse_ctx);
+    let mut export_replacer = ExportReplacer::new(ctx.allocator, ast_builder, source_file);
+    export_replacer.build(&mut ctx.program_mut(), &mut traverse_ctx);
 
     let result = ctx.codegen().build(&ctx.program());
     let result_code = result.source_text.replace("\t", "  ");
 
+    (result_code, export_replacer.errors.len())
+  }
+
+  pub fn assert_fixture(input: &str, expected: &str) {
+    let (result_code, errors) = run_fixture("source.js", input);
+
     assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
+    assert_eq!(errors, 0);
   }
+
+  pub fn assert_fixture_with_errors(input: &str, expected: &str, expected_errors: usize) {
+    let (result_code, errors) = run_fixture("source.js", input);
+
+    assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
+    assert_eq!(errors, expected_errors);
+  }
+
+  pub fn assert_ts_fixture(input: &str, expected: &str) {
+    assert_ts_fixture_with_errors(input, expected, 0);
+  }
+
+  pub fn assert_ts_fixture_with_errors(input: &str, expected: &str, expected_errors: usize) {
+    let (result_code, errors) = run_fixture("source.ts", input);
+
+    assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
+    assert_eq!(errors, expected_errors);
+  }
+}
+
+#[test]
+
+fn test_number_to_js_string() {
+  use crate::export_expand::number_to_js_string;
+
+  assert_eq!(number_to_js_string(0.0), "0");
+  assert_eq!(number_to_js_string(-0.0), "0");
+  assert_eq!(number_to_js_string(-1.5), "-1.5");
+  assert_eq!(number_to_js_string(123.456), "123.456");
+  assert_eq!(number_to_js_string(1e20), "100000000000000000000");
+  assert_eq!(number_to_js_string(1e21), "1e+21");
+  assert_eq!(number_to_js_string(1.5e300), "1.5e+300");
+  assert_eq!(number_to_js_string(0.000001), "0.000001");
+  assert_eq!(number_to_js_string(1e-7), "1e-7");
+  assert_eq!(number_to_js_string(-2.5e-8), "-2.5e-8");
+  assert_eq!(number_to_js_string(f64::NAN), "NaN");
+  assert_eq!(number_to_js_string(f64::NEG_INFINITY), "-Infinity");
 }
 
 #[test]
@@ -630,6 +1118,115 @@ fn test_export_default_obj_anon() {
 
 #[test]
 
+fn test_export_default_call() {
+  let input = r#"
+    import { makeStyles } from "@griffel/core";
+    export default makeStyles({});
+  "#;
+  let output = r#"
+    import { makeStyles } from "@griffel/core";
+    const _export = makeStyles({});
+    export { _export as default };
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
+fn test_export_default_identifier() {
+  let input = r#"
+    const tokens = "tokens";
+    export default tokens;
+  "#;
+  let output = r#"
+    const tokens = "tokens";
+    export { tokens as default };
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
+fn test_export_default_global() {
+  let input = r#"
+    export default window;
+  "#;
+  let output = r#"
+    const _export = window;
+    export { _export as default };
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
+fn test_export_default_reassigned() {
+  let input = r#"
+    let theme = "light";
+    export default theme;
+    theme = "dark";
+  "#;
+  let output = r#"
+    let theme = "light";
+    const _export = theme;
+    export { _export as default };
+    theme = "dark";
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
+fn test_export_default_array() {
+  let input = r#"
+    const a = 1;
+    const b = 2;
+    export default [a, b];
+  "#;
+  let output = r#"
+    const a = 1;
+    const b = 2;
+    const _export = [a, b];
+    export { _export as default };
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
+fn test_export_default_arrow_fn() {
+  let input = r#"
+    export default () => {};
+  "#;
+  let output = r#"
+    const _export = () => {};
+    export { _export as default };
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
+fn test_export_default_literal() {
+  let input = r#"
+    export default "literal";
+  "#;
+  let output = r#"
+    const _export = "literal";
+    export { _export as default };
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
 fn test_export_const() {
   let input = r#"
     export { foo } from "./foo";
@@ -650,6 +1247,24 @@ fn test_export_const() {
 
 #[test]
 
+fn test_export_multiple_declarators() {
+  let input = r#"
+    export const a = 1, { b } = foo, c = a + b;
+  "#;
+  let output = r#"
+    const a = 1;
+    export { a };
+    const { b } = foo;
+    export { b };
+    const c = a + b;
+    export { c };
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
 fn test_export_function() {
   let input = r#"
     export function foo() {}
@@ -684,11 +1299,42 @@ fn test_multi_import() {
 
 #[test]
 
+fn test_import_renamed() {
+  let input = r#"
+    import { foo as bar } from "./mod";
+    export { bar };
+  "#;
+  let output = r#"
+    import { foo as bar } from "./mod";
+    export { bar };
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
+fn test_reexport_renamed() {
+  let input = r#"
+    export { foo as bar } from "./mod";
+  "#;
+  let output = r#"
+    import { foo as bar } from "./mod";
+    export { bar };
+  "#;
+
+  tests::assert_fixture(input, output);
+}
+
+#[test]
+
 fn test_side_effects() {
   let input = r#"
     import "./mod";
   "#;
-  let output = r#""#;
+  let output = r#"
+    import "./mod";
+  "#;
 
   tests::assert_fixture(input, output);
 }
@@ -738,3 +1384,179 @@ fn test_export_obj_pattern() {
 
   tests::assert_fixture(input, output);
 }
+
+#[test]
+
+fn test_unsupported_array_pattern() {
+  let input = r#"
+    const foo = [1, 2];
+    export const [a, b] = foo;
+    export const baz = 3;
+  "#;
+  let output = r#"
+    const foo = [1, 2];
+    export const [a, b] = foo;
+    const baz = 3;
+    export { baz };
+  "#;
+
+  tests::assert_fixture_with_errors(input, output, 1);
+}
+
+#[test]
+
+fn test_unsupported_nested_obj_pattern() {
+  let input = r#"
+    import { foo } from "./foo";
+    export const { a: { b } } = foo;
+  "#;
+  let output = r#"
+    import { foo } from "./foo";
+    export const { a: { b } } = foo;
+  "#;
+
+  tests::assert_fixture_with_errors(input, output, 1);
+}
+
+#[test]
+
+fn test_unsupported_string_literal_reexport() {
+  let input = r#"
+    export { foo as "foo-bar" } from "./foo";
+  "#;
+  let output = r#"
+    export { foo as "foo-bar" } from "./foo";
+  "#;
+
+  tests::assert_fixture_with_errors(input, output, 1);
+}
+
+#[test]
+
+fn test_ts_type_only_exports() {
+  let input = r#"
+    import type { Theme } from "./theme";
+    import { type Props, tokens } from "./tokens";
+    export type Color = string;
+    export interface Styles {}
+    interface Local {}
+    export { Local };
+    export type { Theme };
+    export default interface Foo {}
+    export const color = tokens.color;
+  "#;
+  let output = r#"
+    import { tokens } from "./tokens";
+    const color = tokens.color;
+    export { color };
+  "#;
+
+  tests::assert_ts_fixture(input, output);
+}
+
+#[test]
+
+fn test_ts_declare() {
+  let input = r#"
+    declare const foo: string;
+    export declare const bar: string;
+    export declare function baz(): void;
+    declare global {}
+    export declare namespace Qux {}
+    export namespace Types {
+      export type Foo = string;
+    }
+  "#;
+  let output = r#""#;
+
+  tests::assert_ts_fixture(input, output);
+}
+
+#[test]
+
+fn test_ts_string_enum() {
+  let input = r#"
+    export enum Color {
+      Red = "red",
+      Blue = "blue"
+    }
+  "#;
+  let output = r#"
+    const Color = {
+      Red: "red",
+      Blue: "blue"
+    };
+    export { Color };
+  "#;
+
+  tests::assert_ts_fixture(input, output);
+}
+
+#[test]
+
+fn test_ts_numeric_enum() {
+  let input = r#"
+    enum Direction {
+      Up,
+      Down = 5,
+      Left
+    }
+    export const down = Direction.Down;
+  "#;
+  let output = r#"
+    const Direction = {
+      Up: 0,
+      Down: 5,
+      Left: 6,
+      "0": "Up",
+      "5": "Down",
+      "6": "Left"
+    };
+    const down = Direction.Down;
+    export { down };
+  "#;
+
+  tests::assert_ts_fixture(input, output);
+}
+
+#[test]
+
+fn test_ts_enum_with_references() {
+  let input = r#"
+    enum Flags {
+      A = 1,
+      B = A << 1
+    }
+  "#;
+  let output = r#"
+    enum Flags {
+      A = 1,
+      B = A << 1
+    }
+  "#;
+
+  tests::assert_ts_fixture_with_errors(input, output, 1);
+}
+
+#[test]
+
+fn test_ts_merged_enum() {
+  let input = r#"
+    enum Size {
+      Small = 1
+    }
+    export enum Size {
+      Large = 2
+    }
+  "#;
+  let output = r#"
+    enum Size {
+      Small = 1
+    }
+    export enum Size {
+      Large = 2
+    }
+  "#;
+
+  tests::assert_ts_fixture_with_errors(input, output, 2);
+}
@@ -0,0 +1,354 @@
+use crate::context::TransformContext;
+use crate::ModuleConfig;
+use napi_derive::napi;
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+  CallExpression, Expression, IdentifierReference, ImportDeclarationSpecifier, Program, Statement,
+};
+use oxc_ast::{AstKind, Visit};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_semantic::{ReferenceId, Semantic, SemanticBuilder, SymbolId};
+use oxc_span::{GetSpan, Span};
+
+/// Name of the export that holds arguments of extracted calls in the evaluated module.
+pub const MODULE_EXPORT: &str = "__module";
+
+/// A call of a configured module function, i.e. `makeStyles({ root: { color: "red" } })`.
+#[napi(object)]
+#[derive(Debug)]
+pub struct ExtractedCall {
+  /// Import request of the module that provides the function.
+  pub module_name: String,
+  /// Imported name of the function, the local name may differ.
+  pub specifier: String,
+  /// Start of the call expression in the source text. Offsets are in UTF-8 bytes, the same ones
+  /// that {@link replaceCalls} expects, use `Buffer.from(sourceText).subarray(start, end)` to read
+  /// the call in JS.
+  pub start: u32,
+  /// End of the call expression in the source text, in UTF-8 bytes.
+  pub end: u32,
+  /// Source text of each argument.
+  pub arguments: Vec<String>,
+  /// Index of the first argument in the `__module` array of the evaluated module.
+  pub module_index: u32,
+}
+
+/// A module prepared for evaluation of its extracted calls.
+#[napi(object)]
+pub struct Extraction {
+  /// The module with `export const __module = [...]` appended, it lists arguments of all calls in
+  /// order. The source text is returned as is when there are no calls.
+  pub code: String,
+  /// Extracted calls in source order.
+  pub calls: Vec<ExtractedCall>,
+  /// Parse errors and calls that can't be extracted, i.e. their arguments use function parameters.
+  pub errors: Vec<String>,
+}
+
+/// Finds local symbols of configured imports, i.e. `ms` of
+/// `import { makeStyles as ms } from "@griffel/core"`.
+fn collect_configured_imports(
+  program: &Program,
+  module_config: &[ModuleConfig],
+) -> Vec<(SymbolId, usize, String)> {
+  let mut imports = Vec::new();
+
+  for stmt in &program.body {
+    let Statement::ImportDeclaration(decl) = stmt else {
+      continue;
+    };
+    let Some((config_index, config)) = module_config
+      .iter()
+      .enumerate()
+      .find(|(_, config)| config.module_name == decl.source.value.as_str())
+    else {
+      continue;
+    };
+
+    for specifier in decl.specifiers.iter().flatten() {
+      let ImportDeclarationSpecifier::ImportSpecifier(specifier) = specifier else {
+        continue;
+      };
+      let imported = specifier.imported.name();
+
+      if !config
+        .specifiers
+        .iter()
+        .any(|name| name == imported.as_str())
+      {
+        continue;
+      }
+      if let Some(symbol_id) = specifier.local.symbol_id.get() {
+        imports.push((symbol_id, config_index, imported.to_string()));
+      }
+    }
+  }
+
+  imports
+}
+
+/// Returns the call whose callee is the referenced identifier, other references, i.e. passing the
+/// function as an argument, are not calls.
+fn find_call<'s, 'a>(
+  semantic: &'s Semantic<'a>,
+  reference_node_id: oxc_semantic::AstNodeId,
+) -> Option<&'s CallExpression<'a>> {
+  let nodes = semantic.nodes();
+  let reference_span = nodes.kind(reference_node_id).span();
+
+  let AstKind::CallExpression(call) = nodes.parent_kind(reference_node_id)? else {
+    return None;
+  };
+
+  match call.callee.get_inner_expression() {
+    Expression::Identifier(ident) if ident.span == reference_span => Some(call),
+    _ => None,
+  }
+}
+
+/// Collects identifiers that are read by a node, i.e. by arguments of a call.
+#[derive(Default)]
+struct ReferenceCollector {
+  references: Vec<(ReferenceId, Span, String)>,
+}
+
+impl<'a> Visit<'a> for ReferenceCollector {
+  fn visit_identifier_reference(&mut self, ident: &IdentifierReference<'a>) {
+    if let Some(reference_id) = ident.reference_id.get() {
+      self
+        .references
+        .push((reference_id, ident.span, ident.name.to_string()));
+    }
+  }
+}
+
+/// Reports variables that arguments of a call read from function scopes, i.e. `size` of
+/// `function useStyles(size) { return makeStyles({ root: { width: size } }) }`. The footer lists
+/// arguments in the module scope, where such variables don't exist.
+fn find_non_module_references(semantic: &Semantic, call: &CallExpression) -> Vec<OxcDiagnostic> {
+  let symbols = semantic.symbols();
+  let root_scope_id = semantic.scopes().root_scope_id();
+
+  let mut collector = ReferenceCollector::default();
+  for argument in &call.arguments {
+    collector.visit_argument(argument);
+  }
+
+  collector
+    .references
+    .into_iter()
+    .filter_map(|(reference_id, span, name)| {
+      let symbol_id = symbols.get_reference(reference_id).symbol_id()?;
+      let declaration = symbols.get_span(symbol_id);
+
+      let is_module_scope = symbols.get_scope_id(symbol_id) == root_scope_id;
+      // Parameters of functions within arguments are fine, the whole function is evaluated
+      let is_declared_in_call =
+        call.span.start <= declaration.start && declaration.end <= call.span.end;
+
+      (!is_module_scope && !is_declared_in_call).then(|| {
+        OxcDiagnostic::error(format!("`{name}` is not declared in the module scope"))
+          .with_help(
+            "Arguments of the call are evaluated in the module scope, the call was not extracted",
+          )
+          .with_label(span)
+      })
+    })
+    .collect()
+}
+
+/// Locates calls of configured module functions and appends a footer that exports their
+/// arguments, so they can be evaluated together with the module.
+pub(crate) fn extract_calls_inner(
+  allocator: &Allocator,
+  filename: &String,
+  source_text: &String,
+  module_config: &[ModuleConfig],
+) -> Extraction {
+  let ctx = TransformContext::new(allocator, filename, source_text);
+  let program = ctx.program();
+  let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+    .build(&program)
+    .semantic;
+  let symbols = semantic.symbols();
+
+  let mut calls = Vec::new();
+  let mut diagnostics = Vec::new();
+
+  for (symbol_id, config_index, specifier) in collect_configured_imports(&program, module_config) {
+    for reference_id in symbols.get_resolved_reference_ids(symbol_id) {
+      let reference = symbols.get_reference(*reference_id);
+      let Some(call) = find_call(&semantic, reference.node_id()) else {
+        continue;
+      };
+
+      let non_module_references = find_non_module_references(&semantic, call);
+      if !non_module_references.is_empty() {
+        diagnostics.extend(non_module_references);
+        continue;
+      }
+
+      calls.push(ExtractedCall {
+        module_name: module_config[config_index].module_name.clone(),
+        specifier: specifier.clone(),
+        start: call.span.start,
+        end: call.span.end,
+        arguments: call
+          .arguments
+          .iter()
+          .map(|argument| argument.span().source_text(source_text).to_string())
+          .collect(),
+        module_index: 0,
+      });
+    }
+  }
+
+  calls.sort_by_key(|call| call.start);
+
+  let mut module_index = 0;
+  for call in &mut calls {
+    call.module_index = module_index;
+    module_index += call.arguments.len() as u32;
+  }
+
+  let code = if calls.is_empty() {
+    source_text.clone()
+  } else {
+    let items: Vec<&str> = calls
+      .iter()
+      .flat_map(|call| call.arguments.iter().map(String::as_str))
+      .collect();
+
+    format!(
+      "{source_text}\nexport const {MODULE_EXPORT} = [\n{}\n];\n",
+      items.join(",\n")
+    )
+  };
+
+  ctx.add_diagnostics(diagnostics);
+
+  Extraction {
+    code,
+    calls,
+    errors: ctx.take_and_render_reports(),
+  }
+}
+
+/// Finds calls of configured module functions, i.e. `makeStyles` from `@griffel/core`, and
+/// prepares the module for their evaluation.
+///
+/// @param filename The name of the file being transformed.
+/// @param sourceText the source code itself
+/// @param moduleConfig Modules & their functions whose calls are extracted.
+///
+/// @returns the module with `export const __module = [...]` footer that lists arguments of the
+/// calls, and the calls with their spans.
+#[napi]
+pub fn extract_calls(
+  filename: String,
+  source_text: String,
+  module_config: Vec<ModuleConfig>,
+) -> Extraction {
+  let allocator = Allocator::default();
+
+  extract_calls_inner(&allocator, &filename, &source_text, &module_config)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::extract::{extract_calls, Extraction};
+  use crate::ModuleConfig;
+
+  pub fn extract_griffel_calls(input: &str) -> Extraction {
+    extract_calls(
+      "source.ts".to_string(),
+      input.to_string(),
+      vec![ModuleConfig {
+        module_name: "@griffel/core".to_string(),
+        specifiers: vec!["makeStyles".to_string(), "makeResetStyles".to_string()],
+      }],
+    )
+  }
+}
+
+#[test]
+fn test_extract_calls() {
+  let input = r#"import { makeStyles as ms, makeResetStyles, mergeClasses } from "@griffel/core";
+import { makeStyles } from "other-module";
+
+export const useStyles = ms({ root: { color: "red" } });
+export const useReset = makeResetStyles({ display: "flex" });
+export const useOther = makeStyles({ root: {} });
+export default () => mergeClasses(ms, useStyles().root);"#;
+
+  let result = tests::extract_griffel_calls(input);
+
+  assert_eq!(result.errors.len(), 0);
+  assert_eq!(result.calls.len(), 2);
+  assert_eq!(result.calls[0].specifier, "makeStyles");
+  assert_eq!(
+    result.calls[0].arguments,
+    vec![r#"{ root: { color: "red" } }"#]
+  );
+  assert_eq!(
+    &input[result.calls[0].start as usize..result.calls[0].end as usize],
+    r#"ms({ root: { color: "red" } })"#
+  );
+  assert_eq!(result.calls[1].specifier, "makeResetStyles");
+  assert_eq!(result.calls[1].module_index, 1);
+  assert_eq!(
+    result.code,
+    format!(
+      "{input}\nexport const __module = [\n{},\n{}\n];\n",
+      r#"{ root: { color: "red" } }"#, r#"{ display: "flex" }"#
+    )
+  );
+}
+
+#[test]
+fn test_extract_calls_without_calls() {
+  let input = r#"import { mergeClasses } from "@griffel/core";"#;
+
+  let result = tests::extract_griffel_calls(input);
+
+  assert_eq!(result.calls.len(), 0);
+  assert_eq!(result.code, input);
+}
+
+#[test]
+fn test_extract_calls_function_scope() {
+  let input = r#"import { makeStyles } from "@griffel/core";
+const color = "red";
+export const useStyles = makeStyles({ root: { color } });
+export function useSized(size) {
+  return makeStyles({ root: { color, width: ((value) => value)(size) } });
+}"#;
+
+  let result = tests::extract_griffel_calls(input);
+
+  assert_eq!(result.calls.len(), 1);
+  assert_eq!(result.calls[0].arguments, vec!["{ root: { color } }"]);
+  assert_eq!(result.errors.len(), 1);
+  assert!(result.errors[0].contains("`size` is not declared in the module scope"));
+}
+
+#[test]
+fn test_extract_calls_non_ascii() {
+  let input = r#"import { makeStyles } from "@griffel/core";
+// Стили ✨
+export const useStyles = makeStyles({ root: { content: "«»" } });"#;
+
+  let result = tests::extract_griffel_calls(input);
+  let call = &result.calls[0];
+
+  // Offsets are in UTF-8 bytes, not in UTF-16 code units of JS strings
+  assert_eq!(call.start as usize, input.find("makeStyles({").unwrap());
+  assert_ne!(
+    call.start as usize,
+    input[..call.start as usize].encode_utf16().count()
+  );
+  assert_eq!(
+    &input[call.start as usize..call.end as usize],
+    r#"makeStyles({ root: { content: "«»" } })"#
+  );
+}
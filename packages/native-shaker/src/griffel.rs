@@ -0,0 +1,1143 @@
+use crate::export_expand::number_to_js_string;
+use crate::module_resolve::Assets;
+use crate::options::AssetOptions;
+use napi_derive::napi;
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{Expression, ObjectPropertyKind, UnaryOperator};
+use oxc_parser::Parser;
+use oxc_span::SourceType;
+use rayon::prelude::*;
+
+/// Prefix of class names & keyframe names, see `HASH_PREFIX` in `@griffel/core`.
+const HASH_PREFIX: &str = "f";
+
+/// Buckets of pseudo selectors, keyed by slices of selectors like `getStyleBucketName()` of
+/// `@griffel/core` does it.
+const PSEUDO_BUCKETS: &[(&str, &str)] = &[
+  ("us-w", "w"),
+  ("us-v", "i"),
+  ("nk", "l"),
+  ("si", "v"),
+  ("cu", "f"),
+  ("ve", "h"),
+  ("ti", "a"),
+];
+
+/// Vendor prefixes of hyphenated properties, a subset of the ones the stylis prefixer of
+/// `@griffel/core` adds. Prefixed declarations precede the standard one.
+const PREFIXED_PROPERTIES: &[(&str, &[&str])] = &[
+  ("appearance", &["-webkit-", "-moz-"]),
+  ("backdrop-filter", &["-webkit-"]),
+  ("backface-visibility", &["-webkit-"]),
+  ("box-decoration-break", &["-webkit-"]),
+  ("clip-path", &["-webkit-"]),
+  ("hyphens", &["-webkit-", "-moz-", "-ms-"]),
+  ("mask", &["-webkit-"]),
+  ("mask-clip", &["-webkit-"]),
+  ("mask-composite", &["-webkit-"]),
+  ("mask-image", &["-webkit-"]),
+  ("mask-mode", &["-webkit-"]),
+  ("mask-origin", &["-webkit-"]),
+  ("mask-position", &["-webkit-"]),
+  ("mask-repeat", &["-webkit-"]),
+  ("mask-size", &["-webkit-"]),
+  ("text-size-adjust", &["-webkit-", "-moz-", "-ms-"]),
+  ("user-select", &["-webkit-", "-moz-", "-ms-"]),
+];
+
+/// CSS rules of evaluated `makeStyles()` calls.
+#[napi(object)]
+pub struct ResolvedStyles {
+  /// Class names by slots & hashed property keys serialized to JSON, i.e. the first argument of
+  /// `__styles()`.
+  pub classes: String,
+  /// CSS rules by style buckets serialized to JSON, i.e. the second argument of `__styles()`.
+  pub css_rules: String,
+  /// Rules wrapped with `@griffel:css-start` & `@griffel:css-end` markers, as `transform()`
+  /// returns them in `cssText`.
+  pub css_text: String,
+  /// Invalid style objects & unsupported selectors.
+  pub errors: Vec<String>,
+}
+
+/// A value of a style object. Objects keep the order of their keys as it defines the order of
+/// rules.
+enum StyleValue {
+  Null,
+  String(String),
+  Number(f64),
+  Array(Vec<StyleValue>),
+  Object(Vec<(String, StyleValue)>),
+}
+
+impl StyleValue {
+  fn from_expression(expression: &Expression) -> Result<Self, String> {
+    match expression.get_inner_expression() {
+      Expression::NullLiteral(_) | Expression::BooleanLiteral(_) => Ok(Self::Null),
+      Expression::StringLiteral(lit) => Ok(Self::String(lit.value.to_string())),
+      Expression::NumericLiteral(lit) => Ok(Self::Number(lit.value)),
+      Expression::UnaryExpression(unary) if unary.operator == UnaryOperator::UnaryNegation => {
+        match unary.argument.get_inner_expression() {
+          Expression::NumericLiteral(lit) => Ok(Self::Number(-lit.value)),
+          _ => Err("Styles should be serialized to JSON".to_string()),
+        }
+      }
+      Expression::ArrayExpression(array) => array
+        .elements
+        .iter()
+        .map(|element| {
+          element
+            .as_expression()
+            .ok_or_else(|| "Styles should be serialized to JSON".to_string())
+            .and_then(Self::from_expression)
+        })
+        .collect::<Result<_, _>>()
+        .map(Self::Array),
+      Expression::ObjectExpression(object) => object
+        .properties
+        .iter()
+        .map(|property| {
+          let ObjectPropertyKind::ObjectProperty(property) = property else {
+            return Err("Styles should be serialized to JSON".to_string());
+          };
+          let Some(key) = property.key.static_name() else {
+            return Err("Styles should be serialized to JSON".to_string());
+          };
+
+          Ok((key.to_string(), Self::from_expression(&property.value)?))
+        })
+        .collect::<Result<_, _>>()
+        .map(Self::Object),
+      _ => Err("Styles should be serialized to JSON".to_string()),
+    }
+  }
+
+  /// Returns the value as it's written to CSS, objects & arrays are not CSS values.
+  fn to_css_value(&self) -> Option<String> {
+    match self {
+      Self::String(value) => Some(value.clone()),
+      Self::Number(value) => Some(number_to_js_string(*value)),
+      _ => None,
+    }
+  }
+}
+
+/// Conditions of a rule, i.e. `@media (min-width: 100px)`.
+#[derive(Clone, Default)]
+struct AtRules {
+  container: String,
+  media: String,
+  layer: String,
+  supports: String,
+}
+
+impl AtRules {
+  fn key(&self) -> String {
+    format!(
+      "{}{}{}{}",
+      self.container, self.media, self.layer, self.supports
+    )
+  }
+}
+
+/// Hashes a string with MurmurHash2 the same way as `@emotion/hash` does it, so class names match
+/// ones that `@griffel/core` generates at runtime.
+fn hash_string(value: &str) -> String {
+  const M: u32 = 0x5bd1e995;
+
+  // `charCodeAt(i) & 0xff` in the original implementation
+  let bytes: Vec<u32> = value
+    .encode_utf16()
+    .map(|unit| (unit & 0xff) as u32)
+    .collect();
+  let mut hash: u32 = 0;
+  let mut chunks = bytes.chunks_exact(4);
+
+  for chunk in &mut chunks {
+    let mut k = chunk[0] | (chunk[1] << 8) | (chunk[2] << 16) | (chunk[3] << 24);
+    k = k.wrapping_mul(M);
+    k ^= k >> 24;
+    hash = k.wrapping_mul(M) ^ hash.wrapping_mul(M);
+  }
+
+  let rest = chunks.remainder();
+  if rest.len() == 3 {
+    hash ^= rest[2] << 16;
+  }
+  if rest.len() >= 2 {
+    hash ^= rest[1] << 8;
+  }
+  if !rest.is_empty() {
+    hash ^= rest[0];
+    hash = hash.wrapping_mul(M);
+  }
+
+  hash ^= hash >> 13;
+  hash = hash.wrapping_mul(M);
+  hash ^= hash >> 15;
+
+  to_base36(hash)
+}
+
+fn to_base36(mut value: u32) -> String {
+  const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+  let mut digits = Vec::new();
+
+  loop {
+    digits.push(DIGITS[(value % 36) as usize]);
+    value /= 36;
+
+    if value == 0 {
+      break;
+    }
+  }
+
+  digits.iter().rev().map(|digit| *digit as char).collect()
+}
+
+fn hash_class_name(
+  salt: &str,
+  selector: &str,
+  at_rules: &AtRules,
+  property: &str,
+  value: &str,
+) -> String {
+  format!(
+    "{HASH_PREFIX}{}",
+    hash_string(&format!(
+      "{salt}{selector}{}{property}{}",
+      at_rules.key(),
+      value.trim()
+    ))
+  )
+}
+
+/// Hashes a property with its selector & conditions, classes with the same key override each
+/// other in `mergeClasses()`.
+fn hash_property_key(selector: &str, at_rules: &AtRules, property: &str) -> String {
+  let hash = hash_string(&format!("{selector}{}{property}", at_rules.key()));
+
+  // keys are written to object literals, so they should not start with a digit
+  match hash.as_bytes()[0] {
+    digit @ b'0'..=b'9' => format!("{}{}", (digit + 17) as char, &hash[1..]),
+    _ => hash,
+  }
+}
+
+/// Converts a property name to CSS, i.e. `backgroundColor` to `background-color`.
+fn hyphenate_property(property: &str) -> String {
+  if property.starts_with("--") {
+    return property.to_string();
+  }
+
+  let mut result = String::with_capacity(property.len() + 4);
+  for ch in property.chars() {
+    if ch.is_ascii_uppercase() {
+      result.push('-');
+      result.push(ch.to_ascii_lowercase());
+    } else {
+      result.push(ch);
+    }
+  }
+
+  if result.starts_with("ms-") {
+    result.insert(0, '-');
+  }
+  result
+}
+
+/// Removes whitespace around combinators of a selector, i.e. ` > .foo` becomes `>.foo`.
+fn minify_selector(selector: &str) -> String {
+  let mut result = String::with_capacity(selector.len());
+  let mut has_space = false;
+  let mut after_combinator = false;
+
+  for ch in selector.chars() {
+    if ch.is_whitespace() {
+      has_space = true;
+      continue;
+    }
+
+    let is_combinator = matches!(ch, '>' | '+' | '~');
+    if has_space && !is_combinator && !after_combinator {
+      result.push(' ');
+    }
+
+    result.push(ch);
+    has_space = false;
+    after_combinator = is_combinator;
+  }
+
+  result
+}
+
+fn is_nested_selector(property: &str) -> bool {
+  property.starts_with([':', '[', '>', '&', '~', '+'])
+}
+
+fn combine_queries(current: &str, query: &str) -> String {
+  if current.is_empty() {
+    query.to_string()
+  } else {
+    format!("{current} and {query}")
+  }
+}
+
+fn style_bucket_name(selectors: &[String], at_rules: &AtRules) -> &'static str {
+  if !at_rules.media.is_empty() {
+    return "m";
+  }
+  if !at_rules.layer.is_empty() || !at_rules.supports.is_empty() {
+    return "t";
+  }
+  if !at_rules.container.is_empty() {
+    return "c";
+  }
+
+  let Some(pseudo) = selectors.first().map(|selector| selector.trim()) else {
+    return "d";
+  };
+  if !pseudo.starts_with(':') {
+    return "d";
+  }
+
+  [pseudo.get(4..8), pseudo.get(3..5)]
+    .into_iter()
+    .flatten()
+    .find_map(|slice| {
+      PSEUDO_BUCKETS
+        .iter()
+        .find(|(key, _)| *key == slice)
+        .map(|(_, bucket)| *bucket)
+    })
+    .unwrap_or("d")
+}
+
+/// Removes `url()` functions, paths in them are not directions, i.e. `url(./arrow-left.svg)`.
+fn strip_urls(value: &str) -> String {
+  let mut stripped = String::new();
+  let mut rest = value;
+
+  while let Some(start) = rest.find("url(") {
+    stripped.push_str(&rest[..start]);
+    rest = rest[start..]
+      .find(')')
+      .map_or("", |end| &rest[start + end + 1..]);
+  }
+  stripped.push_str(rest);
+
+  stripped
+}
+
+/// Values that `rtl-css-js` flips but [`convert_property`] does not: horizontal offsets of
+/// shadows & translations, positions, gradients and property names in transitions.
+fn is_unsupported_rtl_value(property: &str, value: &str) -> bool {
+  let property = ["Webkit", "Moz", "ms"]
+    .iter()
+    .find_map(|prefix| property.strip_prefix(prefix))
+    .map_or_else(
+      || property.to_string(),
+      |unprefixed| {
+        let mut chars = unprefixed.chars();
+        chars.next().map_or_else(String::new, |first| {
+          first.to_lowercase().chain(chars).collect()
+        })
+      },
+    );
+  let value = strip_urls(value);
+  let has_direction = value.contains("left") || value.contains("right");
+
+  match property.as_str() {
+    "transform" => value.contains("translate"),
+    "translate" | "boxShadow" | "textShadow" => value.trim() != "none",
+    "backgroundPosition" | "backgroundPositionX" | "transformOrigin" | "perspectiveOrigin" => {
+      has_direction || value.contains('%')
+    }
+    "background" | "backgroundImage" => {
+      has_direction || value.contains('%') || value.contains("gradient(")
+    }
+    "transition" | "transitionProperty" => has_direction,
+    "borderRadius" => value.contains('/'),
+    _ => false,
+  }
+}
+
+/// Flips a declaration for right-to-left documents, it covers common cases of `rtl-css-js`:
+/// left & right properties, values of `float`, `clear` & `textAlign`, and four value shorthands.
+/// Declarations with `/* @noflip */` values are kept as is, other values that `rtl-css-js` would
+/// flip are errors.
+fn convert_property(property: &str, value: &str) -> Result<(String, String), String> {
+  if value.contains("/* @noflip */") {
+    return Ok((property.to_string(), value.to_string()));
+  }
+  if is_unsupported_rtl_value(property, value) {
+    return Err(format!(
+      "\"{property}: {value}\" can't be flipped for right-to-left documents, add /* @noflip */ to \
+       the value to keep it as is"
+    ));
+  }
+
+  let property = match property {
+    "left" => "right".to_string(),
+    "right" => "left".to_string(),
+    _ if property.contains("Left") => property.replace("Left", "Right"),
+    _ if property.contains("Right") => property.replace("Right", "Left"),
+    _ => property.to_string(),
+  };
+
+  let value = match property.as_str() {
+    "float" | "clear" | "textAlign" => match value.trim() {
+      "left" => "right".to_string(),
+      "right" => "left".to_string(),
+      _ => value.to_string(),
+    },
+    "direction" => match value.trim() {
+      "ltr" => "rtl".to_string(),
+      "rtl" => "ltr".to_string(),
+      _ => value.to_string(),
+    },
+    "cursor" => match value.trim() {
+      "e-resize" => "w-resize".to_string(),
+      "w-resize" => "e-resize".to_string(),
+      "ne-resize" => "nw-resize".to_string(),
+      "nw-resize" => "ne-resize".to_string(),
+      "se-resize" => "sw-resize".to_string(),
+      "sw-resize" => "se-resize".to_string(),
+      _ => value.to_string(),
+    },
+    "padding" | "margin" | "borderWidth" | "borderStyle" | "borderColor" | "inset" => {
+      match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [top, right, bottom, left] => format!("{top} {left} {bottom} {right}"),
+        _ => value.to_string(),
+      }
+    }
+    "borderRadius" => match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+      [top_left, top_right, bottom_right, bottom_left] => {
+        format!("{top_right} {top_left} {bottom_left} {bottom_right}")
+      }
+      [top_left, top_right_bottom_left, bottom_right] => {
+        format!("{top_right_bottom_left} {top_left} {top_right_bottom_left} {bottom_right}")
+      }
+      [top_left_bottom_right, top_right_bottom_left] => {
+        format!("{top_right_bottom_left} {top_left_bottom_right}")
+      }
+      _ => value.to_string(),
+    },
+    _ => value.to_string(),
+  };
+
+  Ok((property, value))
+}
+
+/// Writes a declaration with its vendor prefixed variants, see {@link PREFIXED_PROPERTIES}.
+/// `position: sticky` gets a prefixed value instead.
+fn compile_declaration(property: &str, value: &str) -> String {
+  let value = value.trim();
+  let mut css = String::new();
+
+  if property == "position" && value == "sticky" {
+    css.push_str("position:-webkit-sticky;");
+  }
+  if let Some((_, prefixes)) = PREFIXED_PROPERTIES
+    .iter()
+    .find(|(prefixed, _)| *prefixed == property)
+  {
+    for prefix in *prefixes {
+      css.push_str(&format!("{prefix}{property}:{value};"));
+    }
+  }
+  css.push_str(&format!("{property}:{value};"));
+
+  css
+}
+
+/// Writes a rule for a single class, conditions wrap it like `compileAtomicCSSRule()` of
+/// `@griffel/core` does it.
+fn compile_atomic_rule(
+  class_name: &str,
+  selector: &str,
+  at_rules: &AtRules,
+  property: &str,
+  values: &[String],
+) -> String {
+  let property = hyphenate_property(property);
+  let declarations: String = values
+    .iter()
+    .map(|value| compile_declaration(&property, value))
+    .collect();
+  let mut rule = format!(
+    ".{class_name}{}{{{declarations}}}",
+    minify_selector(selector)
+  );
+
+  for (name, condition) in [
+    ("media", &at_rules.media),
+    ("layer", &at_rules.layer),
+    ("supports", &at_rules.supports),
+    ("container", &at_rules.container),
+  ] {
+    if !condition.is_empty() {
+      rule = format!("@{name} {condition}{{{rule}}}");
+    }
+  }
+
+  rule
+}
+
+fn compile_keyframes(frames: &[(String, StyleValue)], rtl: bool) -> Result<String, String> {
+  let mut css = String::new();
+
+  for (selector, declarations) in frames {
+    let StyleValue::Object(declarations) = declarations else {
+      continue;
+    };
+
+    css.push_str(selector);
+    css.push('{');
+    for (property, value) in declarations {
+      let Some(value) = value.to_css_value() else {
+        continue;
+      };
+      let (property, value) = if rtl {
+        convert_property(property, &value)?
+      } else {
+        (property.clone(), value)
+      };
+
+      css.push_str(&format!(
+        "{}:{};",
+        hyphenate_property(&property),
+        value.trim()
+      ));
+    }
+    css.push('}');
+  }
+
+  Ok(css)
+}
+
+/// Class names of a property, a pair when the rule differs in right-to-left documents.
+enum ClassNames {
+  Ltr(String),
+  LtrRtl(String, String),
+}
+
+struct CssRule {
+  css: String,
+  media: String,
+}
+
+/// Sets a value of an ordered map, an existing key keeps its position like object keys in
+/// JavaScript do.
+fn insert_ordered<K: PartialEq, V>(entries: &mut Vec<(K, V)>, key: K, value: V) {
+  match entries.iter_mut().find(|(existing, _)| *existing == key) {
+    Some(entry) => entry.1 = value,
+    None => entries.push((key, value)),
+  }
+}
+
+/// Resolves style objects to atomic rules, mirrors `resolveStyleRules()` of `@griffel/core`.
+struct StyleResolver<'s> {
+  class_name_hash_salt: &'s str,
+  css_rules: Vec<(&'static str, Vec<CssRule>)>,
+  errors: Vec<String>,
+}
+
+impl<'s> StyleResolver<'s> {
+  fn push_css_rule(&mut self, bucket: &'static str, css: String, media: &str) {
+    let rule = CssRule {
+      css,
+      media: media.to_string(),
+    };
+
+    match self.css_rules.iter_mut().find(|(name, _)| *name == bucket) {
+      Some((_, rules)) => rules.push(rule),
+      None => self.css_rules.push((bucket, vec![rule])),
+    }
+  }
+
+  fn resolve(
+    &mut self,
+    styles: &[(String, StyleValue)],
+    selectors: &[String],
+    at_rules: &AtRules,
+    classes: &mut Vec<(String, ClassNames)>,
+    rtl_value: Option<&str>,
+  ) {
+    for (property, value) in styles {
+      match value {
+        StyleValue::Null => {}
+        StyleValue::String(_) | StyleValue::Number(_) => {
+          let value = value.to_css_value().unwrap_or_default();
+          let rtl = match rtl_value {
+            Some(rtl_value) => (property.clone(), rtl_value.to_string()),
+            None => match convert_property(property, &value) {
+              Ok(rtl) => rtl,
+              Err(error) => {
+                self.errors.push(error);
+                continue;
+              }
+            },
+          };
+
+          self.resolve_declaration(
+            property,
+            vec![value],
+            (rtl.0, vec![rtl.1]),
+            selectors,
+            at_rules,
+            classes,
+          );
+        }
+        StyleValue::Array(_) | StyleValue::Object(_) if property == "animationName" => {
+          self.resolve_keyframes(value, selectors, at_rules, classes);
+        }
+        StyleValue::Array(items) => {
+          // fallback values, i.e. `display: ["-webkit-box", "flex"]`
+          let values: Vec<String> = items.iter().filter_map(StyleValue::to_css_value).collect();
+          if values.is_empty() {
+            continue;
+          }
+
+          let converted: Vec<(String, String)> = match values
+            .iter()
+            .map(|value| convert_property(property, value))
+            .collect()
+          {
+            Ok(converted) => converted,
+            Err(error) => {
+              self.errors.push(error);
+              continue;
+            }
+          };
+          let rtl_property = converted[0].0.clone();
+
+          if converted.iter().any(|(key, _)| *key != rtl_property) {
+            self.errors.push(format!(
+              "Fallback values of \"{property}\" are flipped to different properties in RTL"
+            ));
+            continue;
+          }
+
+          self.resolve_declaration(
+            property,
+            values,
+            (
+              rtl_property,
+              converted.into_iter().map(|(_, value)| value).collect(),
+            ),
+            selectors,
+            at_rules,
+            classes,
+          );
+        }
+        StyleValue::Object(nested) => {
+          let mut nested_at_rules = at_rules.clone();
+
+          if is_nested_selector(property) {
+            let mut nested_selectors = selectors.to_vec();
+            nested_selectors.push(property.strip_prefix('&').unwrap_or(property).to_string());
+
+            self.resolve(nested, &nested_selectors, at_rules, classes, None);
+            continue;
+          } else if let Some(query) = property.strip_prefix("@media") {
+            nested_at_rules.media = combine_queries(&at_rules.media, query.trim());
+          } else if let Some(query) = property.strip_prefix("@supports") {
+            nested_at_rules.supports = combine_queries(&at_rules.supports, query.trim());
+          } else if let Some(name) = property.strip_prefix("@layer") {
+            nested_at_rules.layer = if at_rules.layer.is_empty() {
+              name.trim().to_string()
+            } else {
+              format!("{}.{}", at_rules.layer, name.trim())
+            };
+          } else if let Some(query) = property.strip_prefix("@container") {
+            nested_at_rules.container = query.trim().to_string();
+          } else {
+            self
+              .errors
+              .push(format!("\"{property}\" is not a supported nested selector"));
+            continue;
+          }
+
+          self.resolve(nested, selectors, &nested_at_rules, classes, None);
+        }
+      }
+    }
+  }
+
+  fn resolve_declaration(
+    &mut self,
+    property: &str,
+    values: Vec<String>,
+    rtl: (String, Vec<String>),
+    selectors: &[String],
+    at_rules: &AtRules,
+    classes: &mut Vec<(String, ClassNames)>,
+  ) {
+    let selector = selectors.concat();
+    let hash_selector = selector.trim();
+    let salt = self.class_name_hash_salt;
+
+    let property_key = hash_property_key(hash_selector, at_rules, property);
+    let class_name = hash_class_name(salt, hash_selector, at_rules, property, &values.join(";"));
+    let bucket = style_bucket_name(selectors, at_rules);
+
+    self.push_css_rule(
+      bucket,
+      compile_atomic_rule(&class_name, &selector, at_rules, property, &values),
+      &at_rules.media,
+    );
+
+    let (rtl_property, rtl_values) = rtl;
+    if rtl_property == property && rtl_values == values {
+      insert_ordered(classes, property_key, ClassNames::Ltr(class_name));
+      return;
+    }
+
+    let rtl_class_name = hash_class_name(
+      salt,
+      hash_selector,
+      at_rules,
+      &rtl_property,
+      &rtl_values.join(";"),
+    );
+
+    self.push_css_rule(
+      bucket,
+      compile_atomic_rule(
+        &rtl_class_name,
+        &selector,
+        at_rules,
+        &rtl_property,
+        &rtl_values,
+      ),
+      &at_rules.media,
+    );
+    insert_ordered(
+      classes,
+      property_key,
+      ClassNames::LtrRtl(class_name, rtl_class_name),
+    );
+  }
+
+  /// Emits `@keyframes` rules for keyframe objects of `animationName` and resolves the property to
+  /// their hashed names.
+  fn resolve_keyframes(
+    &mut self,
+    value: &StyleValue,
+    selectors: &[String],
+    at_rules: &AtRules,
+    classes: &mut Vec<(String, ClassNames)>,
+  ) {
+    let keyframes: Vec<&StyleValue> = match value {
+      StyleValue::Array(items) => items.iter().collect(),
+      value => vec![value],
+    };
+    let mut names = Vec::new();
+    let mut rtl_names = Vec::new();
+
+    for keyframe in keyframes {
+      let StyleValue::Object(frames) = keyframe else {
+        continue;
+      };
+      let (css, rtl_css) = match (
+        compile_keyframes(frames, false),
+        compile_keyframes(frames, true),
+      ) {
+        (Ok(css), Ok(rtl_css)) => (css, rtl_css),
+        (Err(error), _) | (_, Err(error)) => {
+          self.errors.push(error);
+          return;
+        }
+      };
+      let name = format!("{HASH_PREFIX}{}", hash_string(&css));
+
+      self.push_css_rule("k", format!("@keyframes {name}{{{css}}}"), &at_rules.media);
+
+      if css == rtl_css {
+        rtl_names.push(name.clone());
+      } else {
+        let rtl_name = format!("{HASH_PREFIX}{}", hash_string(&rtl_css));
+
+        self.push_css_rule(
+          "k",
+          format!("@keyframes {rtl_name}{{{rtl_css}}}"),
+          &at_rules.media,
+        );
+        rtl_names.push(rtl_name);
+      }
+      names.push(name);
+    }
+
+    self.resolve(
+      &[(
+        "animationName".to_string(),
+        StyleValue::String(names.join(", ")),
+      )],
+      selectors,
+      at_rules,
+      classes,
+      Some(&rtl_names.join(", ")),
+    );
+  }
+}
+
+fn json_string(value: &str) -> String {
+  serde_json::to_string(value).expect("a string is always serializable")
+}
+
+fn classes_to_json(slots: &[(String, Vec<(String, ClassNames)>)]) -> String {
+  let slots: Vec<String> = slots
+    .iter()
+    .map(|(slot, classes)| {
+      let classes: Vec<String> = classes
+        .iter()
+        .map(|(key, class_names)| match class_names {
+          ClassNames::Ltr(ltr) => format!("{}:{}", json_string(key), json_string(ltr)),
+          ClassNames::LtrRtl(ltr, rtl) => format!(
+            "{}:[{},{}]",
+            json_string(key),
+            json_string(ltr),
+            json_string(rtl)
+          ),
+        })
+        .collect();
+
+      format!("{}:{{{}}}", json_string(slot), classes.join(","))
+    })
+    .collect();
+
+  format!("{{{}}}", slots.join(","))
+}
+
+fn media_to_json(media: &str) -> String {
+  format!("{{\"m\":{}}}", json_string(media))
+}
+
+fn css_rules_to_json(css_rules: &[(&str, Vec<CssRule>)]) -> String {
+  let buckets: Vec<String> = css_rules
+    .iter()
+    .map(|(bucket, rules)| {
+      let rules: Vec<String> = rules
+        .iter()
+        .map(|rule| match rule.media.as_str() {
+          "" => json_string(&rule.css),
+          media => format!("[{},{}]", json_string(&rule.css), media_to_json(media)),
+        })
+        .collect();
+
+      format!("{}:[{}]", json_string(bucket), rules.join(","))
+    })
+    .collect();
+
+  format!("{{{}}}", buckets.join(","))
+}
+
+/// Joins rules to text like the Griffel integration does it in the `extract-css` mode, rules with
+/// media queries are wrapped one by one to keep their conditions for sorting.
+fn css_rules_to_text(css_rules: &[(&str, Vec<CssRule>)], assets: &Assets) -> String {
+  let mut text = String::new();
+
+  for (bucket, rules) in css_rules {
+    if *bucket == "m" {
+      for rule in rules {
+        text.push_str(&format!(
+          "/** @griffel:css-start [{bucket}] [{}] **/\n{}\n/** @griffel:css-end **/\n",
+          media_to_json(&rule.media),
+          rule.css
+        ));
+      }
+    } else {
+      let css: String = rules.iter().map(|rule| rule.css.as_str()).collect();
+
+      text.push_str(&format!(
+        "/** @griffel:css-start [{bucket}] **/\n{css}\n/** @griffel:css-end **/\n"
+      ));
+    }
+  }
+
+  // assets are resolved by the bundler from paths in CSS
+  text.replace(&assets.prefix, "").replace(&assets.suffix, "")
+}
+
+pub(crate) fn resolve_styles_inner(
+  styles_by_slots: &str,
+  class_name_hash_salt: &str,
+  assets: &Assets,
+) -> ResolvedStyles {
+  let allocator = Allocator::default();
+  let mut resolver = StyleResolver {
+    class_name_hash_salt,
+    css_rules: Vec::new(),
+    errors: Vec::new(),
+  };
+  let mut slots = Vec::new();
+
+  let parsed = Parser::new(&allocator, styles_by_slots, SourceType::default())
+    .parse_expression()
+    .map_err(|errors| {
+      errors
+        .into_iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>()
+    })
+    .and_then(|expression| StyleValue::from_expression(&expression).map_err(|error| vec![error]));
+
+  match parsed {
+    Ok(StyleValue::Object(styles_by_slots)) => {
+      for (slot, styles) in styles_by_slots {
+        let StyleValue::Object(styles) = styles else {
+          resolver
+            .errors
+            .push(format!("Styles of the \"{slot}\" slot should be an object"));
+          continue;
+        };
+        let mut classes = Vec::new();
+
+        resolver.resolve(&styles, &[], &AtRules::default(), &mut classes, None);
+        slots.push((slot, classes));
+      }
+    }
+    Ok(_) => resolver
+      .errors
+      .push("Styles should be an object of slots".to_string()),
+    Err(errors) => resolver.errors.extend(errors),
+  }
+
+  ResolvedStyles {
+    classes: classes_to_json(&slots),
+    css_rules: css_rules_to_json(&resolver.css_rules),
+    css_text: css_rules_to_text(&resolver.css_rules, assets),
+    errors: resolver.errors,
+  }
+}
+
+/// Generates atomic CSS for evaluated arguments of `makeStyles()` calls, class names are hashed
+/// the same way as `@griffel/core` does it. Styles are resolved in parallel.
+///
+/// @param styles Style objects by slots serialized to JSON, one per call.
+/// @param classNameHashSalt A salt of class name hashes, it should match the runtime one.
+/// @param assets Placeholders of asset paths that are stripped from the CSS text, they should
+/// match the ones of `transform()`.
+///
+/// @returns class names, CSS rules & the CSS text of each call, in the same order as styles.
+#[napi]
+pub fn resolve_styles(
+  styles: Vec<String>,
+  class_name_hash_salt: Option<String>,
+  assets: Option<AssetOptions>,
+) -> Vec<ResolvedStyles> {
+  let salt = class_name_hash_salt.unwrap_or_default();
+  let assets: Assets = assets.unwrap_or_default().into();
+
+  styles
+    .par_iter()
+    .map(|styles_by_slots| resolve_styles_inner(styles_by_slots, &salt, &assets))
+    .collect()
+}
+
+#[test]
+fn test_hash_string() {
+  assert_eq!(hash_string("color"), "sj55zd");
+  assert_eq!(hash_string("colorred"), "e3e8s9");
+  assert_eq!(
+    hash_property_key("", &AtRules::default(), "color"),
+    "sj55zd"
+  );
+  assert_eq!(hyphenate_property("backgroundColor"), "background-color");
+  assert_eq!(hyphenate_property("msTransform"), "-ms-transform");
+  assert_eq!(hyphenate_property("--color"), "--color");
+}
+
+#[test]
+fn test_resolve_styles() {
+  let result = resolve_styles(
+    vec![
+      r#"{ "root": { "color": "red", ":hover": { "color": "red" } }, "icon": { "zIndex": 1 } }"#
+        .to_string(),
+    ],
+    None,
+    None,
+  );
+  let result = &result[0];
+
+  assert_eq!(result.errors.len(), 0);
+  assert_eq!(
+    result.classes,
+    format!(
+      r#"{{"root":{{"sj55zd":"fe3e8s9","Bi91k9c":"{}"}},"icon":{{"{}":"{}"}}}}"#,
+      hash_class_name("", ":hover", &AtRules::default(), "color", "red"),
+      hash_property_key("", &AtRules::default(), "zIndex"),
+      hash_class_name("", "", &AtRules::default(), "zIndex", "1"),
+    )
+  );
+  assert!(result
+    .css_rules
+    .starts_with(r#"{"d":[".fe3e8s9{color:red;}","#));
+  assert!(result
+    .css_rules
+    .contains(r#""h":[".faf35ka:hover{color:red;}"]"#));
+  assert!(result
+    .css_text
+    .starts_with("/** @griffel:css-start [d] **/\n.fe3e8s9{color:red;}"));
+}
+
+#[test]
+fn test_resolve_styles_conditions() {
+  let result = resolve_styles(
+    vec![r#"{ "root": {
+      "@media (min-width: 100px)": { "color": "red" },
+      "paddingLeft": "4px",
+      "display": ["-webkit-box", "flex"],
+      "@supports (display: grid)": { "display": "grid" }
+    } }"#
+      .to_string()],
+    None,
+    None,
+  );
+  let result = &result[0];
+
+  assert_eq!(result.errors.len(), 0);
+  assert!(result.classes.contains(r#"["fycuoez","f8wuabp"]"#));
+  assert!(result.css_rules.contains(
+    r#""m":[["@media (min-width: 100px){.f18vw7sq{color:red;}}",{"m":"(min-width: 100px)"}]]"#
+  ));
+  assert!(result
+    .css_rules
+    .contains(".fycuoez{padding-left:4px;}\",\".f8wuabp{padding-right:4px;}"));
+  assert!(result
+    .css_rules
+    .contains("{display:-webkit-box;display:flex;}"));
+  assert!(result
+    .css_rules
+    .contains(r#""t":["@supports (display: grid){."#));
+  assert!(result.css_text.starts_with(
+    "/** @griffel:css-start [m] [{\"m\":\"(min-width: 100px)\"}] **/\n@media (min-width: 100px){.f18vw7sq{color:red;}}\n/** @griffel:css-end **/\n"
+  ));
+}
+
+#[test]
+fn test_resolve_styles_errors() {
+  let result = resolve_styles(
+    vec![
+      "{ invalid".to_string(),
+      r#"{ "root": { "@unknown": {} } }"#.to_string(),
+    ],
+    None,
+    None,
+  );
+
+  assert!(!result[0].errors.is_empty());
+  assert_eq!(result[1].errors.len(), 1);
+  assert_eq!(result[1].classes, r#"{"root":{}}"#);
+}
+
+#[test]
+fn test_resolve_styles_declarations() {
+  let result = resolve_styles(
+    vec![r#"{ "root": {
+      "paddingLeft": "4px /* @noflip */",
+      "userSelect": "none",
+      "position": "sticky"
+    } }"#
+      .to_string()],
+    None,
+    None,
+  );
+  let result = &result[0];
+
+  assert_eq!(result.errors.len(), 0);
+  assert!(!result.classes.contains('['));
+  assert!(result
+    .css_rules
+    .contains("{padding-left:4px /* @noflip */;}"));
+  assert!(!result.css_rules.contains("padding-right"));
+  assert!(result.css_rules.contains(
+    "{-webkit-user-select:none;-moz-user-select:none;-ms-user-select:none;user-select:none;}"
+  ));
+  assert!(result
+    .css_rules
+    .contains("{position:-webkit-sticky;position:sticky;}"));
+}
+
+#[test]
+fn test_resolve_styles_assets() {
+  let styles = r#"{ "root": { "backgroundImage": "url(asset:./icon.svg:asset)" } }"#;
+  let result = resolve_styles(
+    vec![styles.to_string()],
+    None,
+    Some(AssetOptions {
+      prefix: Some("asset:".to_string()),
+      suffix: Some(":asset".to_string()),
+      ..Default::default()
+    }),
+  );
+  let result = &result[0];
+
+  assert_eq!(result.errors.len(), 0);
+  assert!(result.css_rules.contains("url(asset:./icon.svg:asset)"));
+  assert!(result
+    .css_text
+    .contains("{background-image:url(./icon.svg);}"));
+}
+
+#[test]
+fn test_resolve_styles_golden() {
+  // output of `makeStyles()` of @griffel/core for the same styles
+  let result = resolve_styles(
+    vec![r#"{ "root": { "color": "red", "paddingLeft": "10px" } }"#.to_string()],
+    None,
+    None,
+  );
+  let result = &result[0];
+
+  assert_eq!(result.errors.len(), 0);
+  assert_eq!(
+    result.classes,
+    r#"{"root":{"sj55zd":"fe3e8s9","uwmqm3":["frdkuqy","f81rol6"]}}"#
+  );
+  assert_eq!(
+    result.css_rules,
+    r#"{"d":[".fe3e8s9{color:red;}",".frdkuqy{padding-left:10px;}",".f81rol6{padding-right:10px;}"]}"#
+  );
+}
+
+#[test]
+fn test_resolve_styles_unsupported_rtl() {
+  let result = resolve_styles(
+    vec![r#"{ "root": {
+      "transform": "translateX(10px)",
+      "boxShadow": "2px 0 4px red",
+      "backgroundPosition": "left top",
+      "transition": "margin-left 1s",
+      "borderRadius": "1px 2px / 3px",
+      "borderTopLeftRadius": "4px",
+      "translate": "none",
+      "backgroundImage": "url(./arrow-left.svg)",
+      "WebkitTransform": "rotate(45deg)",
+      "textShadow": "1px 0 red /* @noflip */"
+    } }"#
+      .to_string()],
+    None,
+    None,
+  );
+  let result = &result[0];
+
+  assert_eq!(result.errors.len(), 5);
+  assert!(result.errors[0].contains("\"transform: translateX(10px)\" can't be flipped"));
+  assert!(result.errors[1].contains("\"boxShadow: 2px 0 4px red\""));
+  assert!(result.errors[2].contains("\"backgroundPosition: left top\""));
+  assert!(result.errors[3].contains("\"transition: margin-left 1s\""));
+  assert!(result.errors[4].contains("\"borderRadius: 1px 2px / 3px\""));
+  assert!(result.css_rules.contains("{border-top-right-radius:4px;}"));
+  assert!(result.css_rules.contains("{translate:none;}"));
+  assert!(result
+    .css_rules
+    .contains("{background-image:url(./arrow-left.svg);}"));
+  assert!(result
+    .css_rules
+    .contains("{-webkit-transform:rotate(45deg);}"));
+  assert!(result
+    .css_rules
+    .contains("{text-shadow:1px 0 red /* @noflip */;}"));
+}
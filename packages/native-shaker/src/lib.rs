@@ -19,9 +19,20 @@ pub struct SourceMap {
 
 pub use crate::options::*;
 
+mod bundle;
+mod commonjs;
 mod crawler;
 mod export_expand;
+mod extract;
+mod griffel;
 mod module_resolve;
+mod object_members;
+mod replace_calls;
+mod side_effects;
+mod statement_graph;
+mod static_eval;
+#[cfg(test)]
+mod test_utils;
 mod transformer;
 
 pub use transformer::*;
@@ -1,5 +1,142 @@
+use crate::{AssetOptions, ResolverOptions};
+use napi_derive::napi;
 use oxc_resolver::{ResolveOptions, Resolver};
-use std::path::{Path, PathBuf};
+use rayon::prelude::*;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+
+/// "sideEffects" field in the package.json file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageSideEffects {
+  /// `"sideEffects": false` marks all modules of the package as side-effect-free.
+  Bool(bool),
+  /// `"sideEffects": ["*.css", "./src/polyfills.js"]` lists modules that have side effects.
+  /// Patterns are absolute: ones without a slash match a file name in any directory, others are
+  /// relative to the package directory.
+  Globs(Vec<String>),
+}
+
+impl PackageSideEffects {
+  fn from_json(value: &Value, package_dir: &Path) -> Option<Self> {
+    match value {
+      Value::Bool(value) => Some(Self::Bool(*value)),
+      Value::String(pattern) => Some(Self::Globs(vec![normalize_glob(pattern, package_dir)])),
+      Value::Array(patterns) => Some(Self::Globs(
+        patterns
+          .iter()
+          .filter_map(Value::as_str)
+          .map(|pattern| normalize_glob(pattern, package_dir))
+          .collect(),
+      )),
+      _ => None,
+    }
+  }
+
+  pub fn has_side_effects(&self, path: &str) -> bool {
+    match self {
+      Self::Bool(value) => *value,
+      Self::Globs(patterns) => patterns
+        .iter()
+        .any(|pattern| glob_match(pattern.as_bytes(), path.as_bytes())),
+    }
+  }
+}
+
+fn normalize_glob(pattern: &str, package_dir: &Path) -> String {
+  if !pattern.contains('/') {
+    return format!("**/{pattern}");
+  }
+
+  let pattern = pattern.trim_start_matches("./");
+  format!("{}/{pattern}", package_dir.to_string_lossy())
+}
+
+/// Matches paths against globs with `**`, `*` and `?`, enough for "sideEffects" fields.
+fn glob_match(pattern: &[u8], path: &[u8]) -> bool {
+  match pattern {
+    [] => path.is_empty(),
+    [b'*', b'*', b'/', rest @ ..] => {
+      glob_match(rest, path)
+        || path
+          .iter()
+          .position(|c| *c == b'/')
+          .is_some_and(|index| glob_match(pattern, &path[index + 1..]))
+    }
+    [b'*', b'*', rest @ ..] => (0..=path.len()).any(|index| glob_match(rest, &path[index..])),
+    [b'*', rest @ ..] => {
+      let segment_len = path.iter().position(|c| *c == b'/').unwrap_or(path.len());
+      (0..=segment_len).any(|index| glob_match(rest, &path[index..]))
+    }
+    [b'?', rest @ ..] => path.first().is_some_and(|c| *c != b'/') && glob_match(rest, &path[1..]),
+    [c, rest @ ..] => path.first() == Some(c) && glob_match(rest, &path[1..]),
+  }
+}
+
+/// Extensions of files that are not JavaScript modules and are handled by bundler loaders.
+pub const DEFAULT_ASSET_EXTENSIONS: &[&str] = &[
+  ".css", ".scss", ".sass", ".less", ".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp", ".avif",
+  ".ico", ".bmp", ".woff", ".woff2", ".ttf", ".eot", ".otf", ".mp4", ".webm", ".mp3", ".wav",
+];
+
+pub const DEFAULT_ASSET_PREFIX: &str = "@wd40:";
+pub const DEFAULT_ASSET_SUFFIX: &str = ":@wd40";
+
+fn has_extension<'e>(request: &str, mut extensions: impl Iterator<Item = &'e str>) -> bool {
+  let path = request.split(['?', '#']).next().unwrap_or(request);
+
+  extensions.any(|extension| path.ends_with(extension))
+}
+
+/// Checks if an import request points to an asset by the default extensions, query & hash are
+/// ignored. Use [`Assets::is_asset`] when extensions are configured.
+pub fn is_asset_request(request: &str) -> bool {
+  has_extension(request, DEFAULT_ASSET_EXTENSIONS.iter().copied())
+}
+
+/// Classifies asset imports by configured extensions & builds placeholders that replace them.
+#[derive(Clone, Debug)]
+pub struct Assets {
+  pub extensions: Vec<String>,
+  pub prefix: String,
+  pub suffix: String,
+}
+
+impl Default for Assets {
+  fn default() -> Self {
+    AssetOptions::default().into()
+  }
+}
+
+impl From<AssetOptions> for Assets {
+  fn from(options: AssetOptions) -> Self {
+    Self {
+      extensions: options.extensions.unwrap_or_else(|| {
+        DEFAULT_ASSET_EXTENSIONS
+          .iter()
+          .map(|extension| extension.to_string())
+          .collect()
+      }),
+      prefix: options
+        .prefix
+        .unwrap_or_else(|| DEFAULT_ASSET_PREFIX.to_string()),
+      suffix: options
+        .suffix
+        .unwrap_or_else(|| DEFAULT_ASSET_SUFFIX.to_string()),
+    }
+  }
+}
+
+impl Assets {
+  pub fn is_asset(&self, request: &str) -> bool {
+    has_extension(request, self.extensions.iter().map(String::as_str))
+  }
+
+  /// Wraps a path of an asset, i.e. `/src/logo.svg` becomes `@wd40:/src/logo.svg:@wd40`.
+  pub fn placeholder(&self, path: &str) -> String {
+    format!("{}{}{}", self.prefix, path, self.suffix)
+  }
+}
 
 #[derive(Debug)]
 pub struct ResolveResult {
@@ -7,6 +144,23 @@ pub struct ResolveResult {
   pub error: Option<String>,
   /// "type" field in the package.json file
   pub module_type: Option<String>,
+  /// "sideEffects" field in the package.json file
+  pub side_effects: Option<PackageSideEffects>,
+  /// "name" field in the package.json file
+  pub package_name: Option<String>,
+  /// "version" field in the package.json file
+  pub package_version: Option<String>,
+}
+
+impl ResolveResult {
+  /// A module is side-effect-free only when its package says so, unresolved modules and packages
+  /// without "sideEffects" are assumed to have side effects.
+  pub fn is_side_effect_free(&self) -> bool {
+    match (&self.path, &self.side_effects) {
+      (Some(path), Some(side_effects)) => !side_effects.has_side_effects(path),
+      _ => false,
+    }
+  }
 }
 
 pub fn resolve(resolver: &Resolver, path: &Path, request: &str) -> ResolveResult {
@@ -19,27 +173,320 @@ pub fn resolve(resolver: &Resolver, path: &Path, request: &str) -> ResolveResult
         .and_then(|p| p.r#type.as_ref())
         .and_then(|t| t.as_str())
         .map(|t| t.to_string()),
+      side_effects: resolution.package_json().and_then(|p| {
+        let package_dir = p.path.parent()?;
+        PackageSideEffects::from_json(p.side_effects.as_ref()?, package_dir)
+      }),
+      package_name: resolution.package_json().and_then(|p| p.name.clone()),
+      package_version: resolution
+        .package_json()
+        .and_then(|p| p.raw_json().get("version"))
+        .and_then(Value::as_str)
+        .map(|v| v.to_string()),
     },
 
     Err(err) => ResolveResult {
       path: None,
       module_type: None,
+      side_effects: None,
+      package_name: None,
+      package_version: None,
       error: Some(err.to_string()),
     },
   }
 }
 
+#[napi(object)]
+pub struct ResolveRequest {
+  /// Import request as written in the code, i.e. `./tokens` or `@griffel/core`.
+  pub specifier: String,
+  /// Path of the importing file, the request is resolved relative to its directory.
+  pub importer: String,
+}
+
+#[napi(object)]
+pub struct ResolvedModule {
+  /// Resolved path, not present when the request can't be resolved.
+  pub path: Option<String>,
+  pub error: Option<String>,
+  /// "type" field in the package.json file of the module.
+  pub module_type: Option<String>,
+  /// "name" field in the package.json file of the module.
+  pub package_name: Option<String>,
+  /// "version" field in the package.json file of the module.
+  pub package_version: Option<String>,
+  /// Assets (images, fonts, styles) are not JavaScript modules.
+  pub is_asset: bool,
+}
+
+impl ResolvedModule {
+  /// Assets are classified by configured extensions, the same ones the crawler uses.
+  fn new(result: ResolveResult, assets: &Assets) -> Self {
+    Self {
+      is_asset: result
+        .path
+        .as_deref()
+        .is_some_and(|path| assets.is_asset(path)),
+      path: result.path,
+      error: result.error,
+      module_type: result.module_type,
+      package_name: result.package_name,
+      package_version: result.package_version,
+    }
+  }
+}
+
+/// Resolves modules with a cache that is kept between calls and shared with crawlers that use
+/// this resolver.
+#[napi]
+#[derive(Clone)]
+pub struct ModuleResolver {
+  resolver: Arc<Resolver>,
+  /// Classifies resolved modules as assets, crawlers use it unless they are given assets.
+  assets: Assets,
+}
+
+impl ModuleResolver {
+  pub fn new(resolve_options: ResolveOptions, assets: Assets) -> Self {
+    Self {
+      resolver: Arc::new(Resolver::new(resolve_options)),
+      assets,
+    }
+  }
+
+  /// Returns the underlying resolver, its cache is shared with the handle.
+  pub fn resolver(&self) -> Arc<Resolver> {
+    Arc::clone(&self.resolver)
+  }
+
+  pub fn assets(&self) -> &Assets {
+    &self.assets
+  }
+}
+
+#[napi]
+impl ModuleResolver {
+  /// Resolves a request relative to the directory of the importing file.
+  #[napi]
+  pub fn resolve(&self, specifier: String, importer: String) -> ResolvedModule {
+    let directory = Path::new(&importer).parent().unwrap_or(Path::new(""));
+
+    ResolvedModule::new(resolve(&self.resolver, directory, &specifier), &self.assets)
+  }
+
+  /// Resolves requests in parallel, results are in the same order as requests.
+  #[napi]
+  pub fn resolve_batch(&self, requests: Vec<ResolveRequest>) -> Vec<ResolvedModule> {
+    requests
+      .par_iter()
+      .map(|request| {
+        let directory = Path::new(&request.importer)
+          .parent()
+          .unwrap_or(Path::new(""));
+
+        ResolvedModule::new(
+          resolve(&self.resolver, directory, &request.specifier),
+          &self.assets,
+        )
+      })
+      .collect()
+  }
+
+  /// Clears cached file system lookups, i.e. after files were changed.
+  #[napi]
+  pub fn clear_cache(&self) {
+    self.resolver.clear_cache();
+  }
+}
+
+/// Creates a resolver, pass it to {@link crawl} to share its cache with the crawler.
+///
+/// @param options Options of module resolution, see {@link ResolverOptions}.
+/// @param assets Extensions of assets, resolved modules are classified by them. Crawlers that use
+/// this resolver classify assets the same way unless they are given their own assets.
+#[napi]
+pub fn create_resolver(
+  options: Option<ResolverOptions>,
+  assets: Option<AssetOptions>,
+) -> ModuleResolver {
+  ModuleResolver::new(
+    options.unwrap_or_default().into(),
+    assets.unwrap_or_default().into(),
+  )
+}
+
 #[test]
 fn test_resolve() {
-  let path =
-    PathBuf::from("/Users/olfedias/WebstormProjects/wd40/packages/native-shaker/src/crawler.rs");
-  let request = "react";
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "node_modules/react/package.json",
+      r#"{ "name": "react", "main": "index.js" }"#,
+    ),
+    ("node_modules/react/index.js", ""),
+    ("src/crawler.ts", ""),
+  ]);
+  let root = fixture.path();
   let resolver = Resolver::new(ResolveOptions::default());
 
-  let result = resolve(&resolver, &path, request);
+  let result = resolve(&resolver, &root.join("src"), "react");
   assert_eq!(result.error, None);
   assert_eq!(
-    result.path,
-    Some("/Users/olfedias/WebstormProjects/wd40/node_modules/react/index.js".to_string())
+    result.path.as_deref(),
+    root.join("node_modules/react/index.js").to_str()
+  );
+  assert_eq!(result.package_name.as_deref(), Some("react"));
+}
+
+#[test]
+fn test_module_resolver() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "node_modules/@scope/pkg/package.json",
+      r#"{ "name": "@scope/pkg", "version": "1.2.3", "type": "module", "main": "index.js" }"#,
+    ),
+    ("node_modules/@scope/pkg/index.js", ""),
+    ("logo.svg", ""),
+  ]);
+  let root = fixture.path();
+  let importer = root.join("entry.ts").to_string_lossy().to_string();
+
+  let resolver = create_resolver(None, None);
+  let module = resolver.resolve("@scope/pkg".to_string(), importer.clone());
+  assert_eq!(
+    module.path.as_deref(),
+    root.join("node_modules/@scope/pkg/index.js").to_str()
+  );
+  assert_eq!(module.module_type.as_deref(), Some("module"));
+  assert_eq!(module.package_name.as_deref(), Some("@scope/pkg"));
+  assert_eq!(module.package_version.as_deref(), Some("1.2.3"));
+  assert!(!module.is_asset);
+
+  let modules = resolver.resolve_batch(vec![
+    ResolveRequest {
+      specifier: "./logo.svg".to_string(),
+      importer: importer.clone(),
+    },
+    ResolveRequest {
+      specifier: "./missing".to_string(),
+      importer,
+    },
+  ]);
+  assert!(modules[0].is_asset);
+  assert_eq!(modules[1].path, None);
+  assert!(modules[1].error.is_some());
+}
+
+#[test]
+fn test_module_resolver_assets() {
+  let fixture = crate::test_utils::Fixture::new(&[("button.style", ""), ("logo.svg", "")]);
+  let importer = fixture
+    .path()
+    .join("entry.ts")
+    .to_string_lossy()
+    .to_string();
+
+  let resolver = create_resolver(
+    None,
+    Some(AssetOptions {
+      extensions: Some(vec![".style".to_string()]),
+      ..AssetOptions::default()
+    }),
+  );
+  let modules = resolver.resolve_batch(vec![
+    ResolveRequest {
+      specifier: "./button.style".to_string(),
+      importer: importer.clone(),
+    },
+    ResolveRequest {
+      specifier: "./logo.svg".to_string(),
+      importer,
+    },
+  ]);
+
+  assert!(modules[0].is_asset);
+  assert!(!modules[1].is_asset);
+}
+
+#[test]
+fn test_module_resolver_aliases() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "tsconfig.json",
+      r#"{ "compilerOptions": { "baseUrl": ".", "paths": { "@tokens/*": ["./src/tokens/*"] } } }"#,
+    ),
+    ("src/tokens/colors.ts", ""),
+    ("src/theme.ts", ""),
+    ("overrides/theme.ts", ""),
+  ]);
+  let root = fixture.path();
+  let importer = root.join("entry.ts").to_string_lossy().to_string();
+  let to_path = |path: &str| root.join(path).to_string_lossy().to_string();
+
+  let resolver = create_resolver(
+    Some(ResolverOptions {
+      alias: Some(vec![
+        ("@/theme".to_string(), vec![to_path("overrides/theme.ts")]),
+        ("@".to_string(), vec![to_path("src")]),
+      ]),
+      tsconfig: Some(to_path("tsconfig.json")),
+      ..ResolverOptions::default()
+    }),
+    None,
+  );
+
+  let module = resolver.resolve("@tokens/colors".to_string(), importer.clone());
+  assert_eq!(module.path, Some(to_path("src/tokens/colors.ts")));
+
+  // The first matching alias wins
+  let module = resolver.resolve("@/theme".to_string(), importer);
+  assert_eq!(module.path, Some(to_path("overrides/theme.ts")));
+}
+
+#[test]
+fn test_package_side_effects() {
+  let package_dir = Path::new("/project/node_modules/pkg");
+
+  let side_effects = PackageSideEffects::from_json(&serde_json::json!(false), package_dir).unwrap();
+  assert!(!side_effects.has_side_effects("/project/node_modules/pkg/index.js"));
+
+  let side_effects = PackageSideEffects::from_json(
+    &serde_json::json!(["*.css", "./src/polyfills/**/*.js", "./index.js"]),
+    package_dir,
+  )
+  .unwrap();
+  assert!(side_effects.has_side_effects("/project/node_modules/pkg/src/styles/button.css"));
+  assert!(side_effects.has_side_effects("/project/node_modules/pkg/src/polyfills/array.js"));
+  assert!(side_effects.has_side_effects("/project/node_modules/pkg/src/polyfills/es/map.js"));
+  assert!(side_effects.has_side_effects("/project/node_modules/pkg/index.js"));
+  assert!(!side_effects.has_side_effects("/project/node_modules/pkg/src/tokens.js"));
+  assert!(!side_effects.has_side_effects("/project/node_modules/pkg/lib/index.js"));
+}
+
+#[test]
+fn test_is_asset_request() {
+  assert!(is_asset_request("./global.css"));
+  assert!(is_asset_request("@scope/pkg/styles.scss"));
+  assert!(is_asset_request("./icon.svg?react"));
+  assert!(!is_asset_request("./polyfill"));
+  assert!(!is_asset_request("./styles.css.js"));
+}
+
+#[test]
+fn test_assets() {
+  let assets = Assets::from(AssetOptions {
+    extensions: Some(vec![".style".to_string()]),
+    prefix: Some("__asset(".to_string()),
+    ..AssetOptions::default()
+  });
+
+  assert!(assets.is_asset("./button.style?inline"));
+  assert!(!assets.is_asset("./logo.svg"));
+  assert_eq!(
+    assets.placeholder("/src/button.style"),
+    "__asset(/src/button.style:@wd40"
+  );
+  assert_eq!(
+    Assets::default().placeholder("/src/logo.svg"),
+    "@wd40:/src/logo.svg:@wd40"
   );
 }
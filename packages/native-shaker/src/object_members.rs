@@ -0,0 +1,358 @@
+use crate::side_effects::SideEffects;
+use oxc_ast::ast::{
+  BindingPatternKind, Expression, ObjectPropertyKind, Program, PropertyKind, Statement,
+  UnaryOperator, VariableDeclarationKind,
+};
+use oxc_ast::AstKind;
+use oxc_semantic::{AstNodeId, Semantic, SymbolId};
+use oxc_span::GetSpan;
+use std::collections::{HashMap, HashSet};
+
+/// Requested exports split to names & properties, i.e. `tokens.colorBrand` requests only the
+/// `colorBrand` property of the `tokens` export.
+pub(crate) struct ExportRequests<'r> {
+  /// Names of requested exports, including exports that are requested by their properties.
+  pub names: Vec<&'r str>,
+  /// Properties of exports that are requested only by property paths, nested paths request their
+  /// top level property.
+  pub members: HashMap<&'r str, HashSet<&'r str>>,
+}
+
+impl<'r> ExportRequests<'r> {
+  /// Requests that are names of exports are not property paths, i.e. `a.b` of
+  /// `export { x as "a.b" }`.
+  pub fn new(only_exports: &[&'r str], is_export: impl Fn(&str) -> bool) -> Self {
+    let mut names = Vec::new();
+    let mut members: HashMap<&str, HashSet<&str>> = HashMap::new();
+    let mut whole_exports = HashSet::new();
+
+    for request in only_exports {
+      let property_path = request.split_once('.').filter(|_| !is_export(request));
+      let (name, member) = match property_path {
+        Some((name, path)) => (
+          name,
+          Some(path.split_once('.').map_or(path, |(key, _)| key)),
+        ),
+        None => (*request, None),
+      };
+
+      if !names.contains(&name) {
+        names.push(name);
+      }
+
+      match member {
+        Some(member) => {
+          members.entry(name).or_default().insert(member);
+        }
+        None => {
+          whole_exports.insert(name);
+        }
+      }
+    }
+
+    members.retain(|name, _| !whole_exports.contains(name));
+
+    Self { names, members }
+  }
+}
+
+/// An exported object literal, properties that are not in `keep` are not used.
+pub(crate) struct PrunableObject {
+  statement_index: usize,
+  declarator_index: usize,
+  keep: HashSet<String>,
+}
+
+/// Checks if a property can be removed without changing values of other properties, methods &
+/// functions can read siblings with `this`. Keys of spreads are not known, a removed property could
+/// have overridden a requested one or a getter of the spread object could read it.
+fn is_plain_property(property: &ObjectPropertyKind) -> bool {
+  match property {
+    ObjectPropertyKind::SpreadProperty(_) => false,
+    ObjectPropertyKind::ObjectProperty(property) => {
+      property.kind == PropertyKind::Init
+        && !property.method
+        && property.key.static_name().is_some()
+        && !matches!(
+          property.value.get_inner_expression(),
+          Expression::FunctionExpression(_)
+        )
+    }
+  }
+}
+
+/// Returns the name of a property that a reference reads, i.e. `colorBrand` of
+/// `tokens.colorBrand`. Other uses, like passing the object to a function, writing its properties
+/// or calling its methods, let the object escape, so `None` is returned.
+fn read_property_name(semantic: &Semantic, node_id: AstNodeId) -> Option<String> {
+  let nodes = semantic.nodes();
+  let reference_span = nodes.kind(node_id).span();
+  let member_id = nodes.parent_id(node_id)?;
+
+  let AstKind::MemberExpression(member) = nodes.kind(member_id) else {
+    return None;
+  };
+  if member.object().without_parentheses().span() != reference_span {
+    return None;
+  }
+  let name = member.static_property_name()?;
+
+  match nodes.parent_kind(member_id) {
+    Some(
+      AstKind::SimpleAssignmentTarget(_)
+      | AstKind::AssignmentTarget(_)
+      | AstKind::UpdateExpression(_),
+    ) => None,
+    Some(AstKind::UnaryExpression(unary)) if unary.operator == UnaryOperator::Delete => None,
+    Some(AstKind::CallExpression(call)) if call.callee.span() == member.span() => None,
+    _ => Some(name.to_string()),
+  }
+}
+
+/// Names of exports of a program, `ExportReplacer` has already normalized them to specifiers.
+pub(crate) fn exported_names(program: &Program) -> HashSet<String> {
+  let mut names = HashSet::new();
+
+  for stmt in &program.body {
+    match stmt {
+      Statement::ExportNamedDeclaration(decl) => {
+        names.extend(
+          decl
+            .specifiers
+            .iter()
+            .map(|specifier| specifier.exported.name().to_string()),
+        );
+      }
+      Statement::ExportAllDeclaration(decl) => {
+        names.extend(
+          decl
+            .exported
+            .as_ref()
+            .map(|exported| exported.name().to_string()),
+        );
+      }
+      _ => {}
+    }
+  }
+
+  names
+}
+
+/// Returns properties of an imported binding that the module reads, i.e. `colorBrand` of
+/// `tokens.colorBrand`. A re-export of the binding reads properties that are requested from it.
+/// `None` is returned when the binding is used as a whole.
+pub(crate) fn find_imported_members(
+  program: &Program,
+  semantic: &Semantic,
+  symbol_id: SymbolId,
+  requests: &ExportRequests,
+) -> Option<Vec<String>> {
+  let symbols = semantic.symbols();
+  let reference_ids = symbols.get_resolved_reference_ids(symbol_id);
+  if reference_ids.is_empty() {
+    return None;
+  }
+
+  let mut members = Vec::new();
+
+  for reference_id in reference_ids {
+    let node_id = symbols.get_reference(*reference_id).node_id();
+
+    if let Some(name) = read_property_name(semantic, node_id) {
+      members.push(name);
+      continue;
+    }
+
+    // `ExportReplacer` has already normalized exports to `export { local as exported }`
+    let span = semantic.nodes().kind(node_id).span();
+    let exported = program
+      .body
+      .iter()
+      .filter_map(|stmt| match stmt {
+        Statement::ExportNamedDeclaration(decl) if decl.source.is_none() => Some(decl),
+        _ => None,
+      })
+      .flat_map(|decl| &decl.specifiers)
+      .find(|specifier| specifier.local.span() == span)?;
+    let requested = requests.members.get(exported.exported.name().as_str())?;
+
+    members.extend(requested.iter().map(|name| name.to_string()));
+  }
+
+  Some(members)
+}
+
+/// Finds exported `const` object literals whose properties are requested by property paths and
+/// that do not escape the module, i.e. are only exported & read with static member expressions.
+/// Properties that are read within the module or have side effects are kept.
+pub(crate) fn find_prunable_objects(
+  program: &Program,
+  semantic: &Semantic,
+  side_effects: &SideEffects,
+  members: &HashMap<&str, HashSet<&str>>,
+) -> Vec<PrunableObject> {
+  // `ExportReplacer` has already normalized exports to `export { local as exported }`
+  let mut exported_members: HashMap<String, Option<HashSet<String>>> = HashMap::new();
+  let mut export_spans = HashSet::new();
+
+  for stmt in &program.body {
+    let Statement::ExportNamedDeclaration(decl) = stmt else {
+      continue;
+    };
+    if decl.source.is_some() {
+      continue;
+    }
+
+    for specifier in &decl.specifiers {
+      let requested = members.get(specifier.exported.name().as_str());
+      let entry = exported_members
+        .entry(specifier.local.name().to_string())
+        .or_insert_with(|| Some(HashSet::new()));
+
+      *entry = match (entry.take(), requested) {
+        (Some(mut keep), Some(requested)) => {
+          keep.extend(requested.iter().map(|name| name.to_string()));
+          Some(keep)
+        }
+        _ => None,
+      };
+      export_spans.insert(specifier.local.span());
+    }
+  }
+
+  let symbols = semantic.symbols();
+  let is_exported_reference =
+    |node_id: AstNodeId| -> bool { export_spans.contains(&semantic.nodes().kind(node_id).span()) };
+  let mut objects = Vec::new();
+
+  for (statement_index, stmt) in program.body.iter().enumerate() {
+    let Statement::VariableDeclaration(decl) = stmt else {
+      continue;
+    };
+    if decl.kind != VariableDeclarationKind::Const {
+      continue;
+    }
+
+    for (declarator_index, declarator) in decl.declarations.iter().enumerate() {
+      let (
+        BindingPatternKind::BindingIdentifier(ident),
+        Some(Expression::ObjectExpression(object)),
+      ) = (&declarator.id.kind, &declarator.init)
+      else {
+        continue;
+      };
+      let Some(Some(requested)) = exported_members.get(ident.name.as_str()) else {
+        continue;
+      };
+      let Some(symbol_id) = ident.symbol_id.get() else {
+        continue;
+      };
+      if !object.properties.iter().all(is_plain_property) {
+        continue;
+      }
+
+      let mut keep = requested.clone();
+      let mut escapes = false;
+
+      for reference_id in symbols.get_resolved_reference_ids(symbol_id) {
+        let node_id = symbols.get_reference(*reference_id).node_id();
+
+        if is_exported_reference(node_id) {
+          continue;
+        }
+        match read_property_name(semantic, node_id) {
+          Some(name) => {
+            keep.insert(name);
+          }
+          None => {
+            escapes = true;
+            break;
+          }
+        }
+      }
+
+      if escapes {
+        continue;
+      }
+
+      let mut is_pruned = false;
+      for property in &object.properties {
+        let ObjectPropertyKind::ObjectProperty(property) = property else {
+          continue;
+        };
+        let Some(key) = property.key.static_name() else {
+          continue;
+        };
+
+        if keep.contains(key.as_ref()) {
+          continue;
+        }
+        if side_effects.expression_may_have_side_effects(&property.value) {
+          keep.insert(key.to_string());
+        } else {
+          is_pruned = true;
+        }
+      }
+
+      if is_pruned {
+        objects.push(PrunableObject {
+          statement_index,
+          declarator_index,
+          keep,
+        });
+      }
+    }
+  }
+
+  objects
+}
+
+/// Removes properties of objects found by [`find_prunable_objects`].
+pub(crate) fn prune_object_members(program: &mut Program, objects: &[PrunableObject]) {
+  for object in objects {
+    let Statement::VariableDeclaration(decl) = &mut program.body[object.statement_index] else {
+      continue;
+    };
+    let Some(Expression::ObjectExpression(init)) =
+      &mut decl.declarations[object.declarator_index].init
+    else {
+      continue;
+    };
+
+    init.properties.retain(|property| match property {
+      ObjectPropertyKind::SpreadProperty(_) => true,
+      ObjectPropertyKind::ObjectProperty(property) => property
+        .key
+        .static_name()
+        .map_or(true, |key| object.keep.contains(key.as_ref())),
+    });
+  }
+}
+
+#[test]
+fn test_export_requests() {
+  let requests = ExportRequests::new(
+    &[
+      "tokens.colorA",
+      "tokens.colorB.hover",
+      "theme",
+      "theme.spacing",
+    ],
+    |_| false,
+  );
+  let mut members: Vec<&str> = requests.members["tokens"].iter().copied().collect();
+  members.sort_unstable();
+
+  assert_eq!(requests.names, vec!["tokens", "theme"]);
+  assert_eq!(members, vec!["colorA", "colorB"]);
+  assert!(!requests.members.contains_key("theme"));
+}
+
+#[test]
+fn test_export_requests_string_names() {
+  let requests = ExportRequests::new(&["a.b", "tokens.color"], |name| name == "a.b");
+
+  assert_eq!(requests.names, vec!["a.b", "tokens"]);
+  assert!(!requests.members.contains_key("a"));
+  assert!(requests.members["tokens"].contains("color"));
+}
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use napi::Either;
 use napi_derive::napi;
+use oxc_resolver::{AliasValue, ResolveOptions, TsconfigOptions, TsconfigReferences};
 use oxc_transformer::{
   ArrowFunctionsOptions, ES2015Options, ReactOptions, RewriteExtensionsMode, TypeScriptOptions,
 };
@@ -106,6 +107,80 @@ impl From<ES2015BindingOptions> for ES2015Options {
   }
 }
 
+/// What happens to statements that have side effects, are not required by requested exports, but
+/// use bindings that are kept, i.e. `registerTokens(theme)` or `theme.color = "red"`.
+#[napi(string_enum)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SideEffectsMode {
+  /// Remove such statements, values of kept bindings may differ from the original module.
+  #[default]
+  #[napi(value = "drop")]
+  Drop,
+  /// Remove such statements and add a warning for each of them to `errors`.
+  #[napi(value = "report")]
+  Report,
+  /// Keep such statements and everything they depend on.
+  #[napi(value = "keep")]
+  Keep,
+}
+
+/// Which bare imports, i.e. `import "./polyfill"` or `import "./global.css"`, are kept in the
+/// output. Kept imports are never removed by tree shaking.
+#[napi(string_enum)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BareImportsPolicy {
+  /// Keep all bare imports.
+  #[napi(value = "keep")]
+  Keep,
+  /// Remove all bare imports.
+  #[napi(value = "drop")]
+  Drop,
+  /// Keep bare imports except imports of assets, i.e. `.css` or `.svg` files.
+  #[napi(value = "keep-unless-asset")]
+  KeepUnlessAsset,
+  /// Remove bare imports of modules that are declared side-effect-free by the "sideEffects" field
+  /// of their package.json, keep others.
+  #[default]
+  #[napi(value = "drop-if-package-side-effect-free")]
+  DropIfPackageSideEffectFree,
+}
+
+/// Configure how imports of assets, i.e. `import logo from "./logo.svg"`, are handled. They are
+/// replaced with string constants holding a placeholder of the resolved path, bundlers swap it
+/// with a URL of the emitted file.
+#[napi(object)]
+#[derive(Clone, Debug, Default)]
+pub struct AssetOptions {
+  /// Extensions of files that are treated as assets.
+  ///
+  /// @default ['.css', '.scss', '.sass', '.less', '.png', '.jpg', '.jpeg', '.gif', '.svg', ...]
+  pub extensions: Option<Vec<String>>,
+
+  /// Prepended to a path of an asset in the placeholder.
+  ///
+  /// @default '@wd40:'
+  pub prefix: Option<String>,
+
+  /// Appended to a path of an asset in the placeholder.
+  ///
+  /// @default ':@wd40'
+  pub suffix: Option<String>,
+}
+
+/// Functions of a module whose calls are extracted for evaluation, i.e. `makeStyles` from
+/// `@griffel/core`.
+///
+/// @see {@link extractCalls}
+#[napi(object)]
+#[derive(Clone, Debug, Default)]
+pub struct ModuleConfig {
+  /// Import request of the module as written in the code.
+  pub module_name: String,
+
+  /// Names of the module exports, calls of them are extracted.
+  pub specifiers: Vec<String>,
+}
+
 /// Options for transforming a JavaScript or TypeScript file.
 ///
 /// @see {@link transform}
@@ -121,6 +196,42 @@ pub struct TransformOptions {
 
   /// Configure how TSX and JSX are transformed.
   pub react: Option<ReactBindingOptions>,
+
+  /// Enable ES2015 transformations.
+  pub es2015: Option<ES2015BindingOptions>,
+
+  /// Enable source map generation.
+  ///
+  /// When `true`, the `map` field of transform result objects will be populated and the output
+  /// is the generated code instead of the JSON AST.
+  ///
+  /// @default false
+  pub sourcemap: Option<bool>,
+
+  /// Configure how statements with side effects that mutate kept bindings are handled.
+  ///
+  /// @default 'drop'
+  pub side_effects: Option<SideEffectsMode>,
+
+  /// Consider reads of properties of imports & globals to have side effects, as their getters can
+  /// run arbitrary code. Statements with such reads are handled according to `sideEffects`.
+  ///
+  /// @default false
+  pub property_read_side_effects: Option<bool>,
+
+  /// Configure which bare imports are kept in the output.
+  ///
+  /// @default 'drop-if-package-side-effect-free'
+  pub bare_imports: Option<BareImportsPolicy>,
+
+  /// Replace imports of assets with string constants. Assets are imported as is when omitted.
+  pub assets: Option<AssetOptions>,
+
+  /// Lower imports & exports to `require()` calls and assignments to `exports`, so the output can
+  /// be evaluated as a CommonJS module. Import requests are replaced with resolved paths.
+  ///
+  /// @default false
+  pub commonjs: Option<bool>,
 }
 
 impl From<TransformOptions> for oxc_transformer::TransformOptions {
@@ -129,7 +240,96 @@ impl From<TransformOptions> for oxc_transformer::TransformOptions {
       cwd: options.cwd.map(PathBuf::from).unwrap_or_default(),
       typescript: options.typescript.map(Into::into).unwrap_or_default(),
       react: options.react.map(Into::into).unwrap_or_default(),
+      es2015: options.es2015.map(Into::into).unwrap_or_default(),
       ..Self::default()
     }
   }
 }
+
+/// Module resolution options, they mirror options of bundlers' resolvers.
+///
+/// @see [oxc-resolver](https://github.com/oxc-project/oxc-resolver#options)
+#[napi(object)]
+#[derive(Default)]
+pub struct ResolverOptions {
+  /// Extensions that are tried in order when a request has none.
+  ///
+  /// @default ['.js', '.jsx', '.ts', '.tsx', '.json', '.mjs', '.cjs', '.mts', '.cts']
+  pub extensions: Option<Vec<String>>,
+
+  /// Conditions that are matched in "exports" & "imports" fields of package.json.
+  ///
+  /// @default []
+  pub condition_names: Option<Vec<String>>,
+
+  /// Fields of package.json that are used as exports.
+  ///
+  /// @default ['import']
+  pub exports_fields: Option<Vec<String>>,
+
+  /// Fields of package.json that point to the entry of a package.
+  ///
+  /// @default ['module', 'main']
+  pub main_fields: Option<Vec<String>>,
+
+  /// Replaces requests that start with a key by one of paths, i.e. `[['@', ['./src']]]`. Aliases
+  /// are tried in order, so more specific keys should go first.
+  pub alias: Option<Vec<(String, Vec<String>)>>,
+
+  /// Path to a `tsconfig.json` file, its `paths` are used to resolve requests.
+  pub tsconfig: Option<String>,
+
+  /// Resolve symlinks to their real paths.
+  ///
+  /// @default true
+  pub symlinks: Option<bool>,
+
+  /// Directories where server-relative requests (`/src/file.js`) are resolved.
+  ///
+  /// @default []
+  pub roots: Option<Vec<String>>,
+}
+
+impl From<ResolverOptions> for ResolveOptions {
+  fn from(options: ResolverOptions) -> Self {
+    let ops = ResolveOptions::default();
+    let to_strings =
+      |values: &[&str]| -> Vec<String> { values.iter().map(|value| value.to_string()).collect() };
+
+    ResolveOptions {
+      extensions: options.extensions.unwrap_or_else(|| {
+        to_strings(&[
+          ".js", ".jsx", ".ts", ".tsx", ".json", ".mjs", ".cjs", ".mts", ".cts",
+        ])
+      }),
+      condition_names: options.condition_names.unwrap_or(ops.condition_names),
+      exports_fields: options
+        .exports_fields
+        .unwrap_or_else(|| to_strings(&["import"]))
+        .into_iter()
+        .map(|field| vec![field])
+        .collect(),
+      main_fields: options
+        .main_fields
+        .unwrap_or_else(|| to_strings(&["module", "main"])),
+      alias: options
+        .alias
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, paths)| (key, paths.into_iter().map(AliasValue::Path).collect()))
+        .collect(),
+      tsconfig: options.tsconfig.map(|config_file| TsconfigOptions {
+        config_file: PathBuf::from(config_file),
+        references: TsconfigReferences::Auto,
+      }),
+      symlinks: options.symlinks.unwrap_or(ops.symlinks),
+      roots: options
+        .roots
+        .unwrap_or_default()
+        .into_iter()
+        .map(PathBuf::from)
+        .collect(),
+      ..ops
+    }
+  }
+}
@@ -0,0 +1,503 @@
+use crate::context::TransformContext;
+use crate::side_effects::SideEffects;
+use crate::transformer::{
+  bare_import_source, find_reachable_statements, pass_to_output, retain_statements, TransformInner,
+};
+use crate::SideEffectsMode;
+use napi_derive::napi;
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+  BindingIdentifier, Expression, IdentifierName, ImportDeclaration, ImportDeclarationSpecifier,
+  Statement,
+};
+use oxc_ast::visit::walk_mut;
+use oxc_ast::{AstBuilder, Visit, VisitMut};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_parser::Parser;
+use oxc_semantic::SemanticBuilder;
+use oxc_span::{GetSpanMut, SourceType, Span, SPAN};
+use std::collections::{HashMap, HashSet};
+
+/// Substituted with the payload of a call in {@link ReplaceCallsOptions#template}.
+pub const PAYLOAD_PLACEHOLDER: &str = "<json>";
+
+/// A call that is replaced with the template, i.e. an extracted `makeStyles()` call.
+#[napi(object)]
+pub struct CallReplacement {
+  /// Start of the call expression in the source text.
+  pub start: u32,
+  /// End of the call expression in the source text.
+  pub end: u32,
+  /// JSON that is substituted into the template, i.e. precomputed classes.
+  pub payload: String,
+}
+
+/// An import that the template needs, i.e. `import { __styles as _styles } from "@griffel/core"`.
+#[napi(object)]
+pub struct TemplateImport {
+  pub module_name: String,
+  pub imported: String,
+  pub local: String,
+}
+
+#[napi(object)]
+pub struct ReplaceCallsOptions {
+  /// Code of an expression that replaces every call, `<json>` is substituted with the payload.
+  ///
+  /// @example '_styles(<json>)'
+  pub template: String,
+
+  /// Imports that are added to the top of the module when at least one call is replaced.
+  pub imports: Option<Vec<TemplateImport>>,
+
+  /// Enable source map generation.
+  ///
+  /// @default false
+  pub sourcemap: Option<bool>,
+}
+
+/// Replaces call expressions with parsed templates, calls are found by their spans in the source
+/// text.
+struct CallReplacer<'a> {
+  replacements: HashMap<(u32, u32), Expression<'a>>,
+}
+
+impl<'a> VisitMut<'a> for CallReplacer<'a> {
+  fn visit_expression(&mut self, expr: &mut Expression<'a>) {
+    if let Expression::CallExpression(call) = expr {
+      if let Some(replacement) = self.replacements.remove(&(call.span.start, call.span.end)) {
+        *expr = replacement;
+        return;
+      }
+    }
+
+    walk_mut::walk_expression(self, expr);
+  }
+}
+
+/// Moves nodes of a parsed template to the span of the call it replaces, so source maps point to
+/// the original call instead of offsets in the template.
+struct SpanReplacer {
+  span: Span,
+}
+
+impl<'a> VisitMut<'a> for SpanReplacer {
+  fn visit_expression(&mut self, expr: &mut Expression<'a>) {
+    *expr.span_mut() = self.span;
+    walk_mut::walk_expression(self, expr);
+  }
+
+  fn visit_identifier_name(&mut self, ident: &mut IdentifierName<'a>) {
+    ident.span = self.span;
+  }
+}
+
+/// Resets spans of injected imports, they don't exist in the source text and offsets of the parsed
+/// import code would point to unrelated parts of the original module in source maps.
+struct ImportSpanResetter;
+
+impl<'a> VisitMut<'a> for ImportSpanResetter {
+  fn visit_import_declaration(&mut self, decl: &mut ImportDeclaration<'a>) {
+    decl.span = SPAN;
+    decl.source.span = SPAN;
+
+    for specifier in decl.specifiers.iter_mut().flatten() {
+      match specifier {
+        ImportDeclarationSpecifier::ImportSpecifier(specifier) => {
+          specifier.span = SPAN;
+          *specifier.imported.span_mut() = SPAN;
+          specifier.local.span = SPAN;
+        }
+        ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+          specifier.span = SPAN;
+          specifier.local.span = SPAN;
+        }
+        ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+          specifier.span = SPAN;
+          specifier.local.span = SPAN;
+        }
+      }
+    }
+  }
+}
+
+/// Finds a binding with the name in any scope, a template import with the same local name would
+/// conflict with it or be shadowed by it.
+struct BindingFinder<'n> {
+  name: &'n str,
+  span: Option<Span>,
+}
+
+impl<'a, 'n> Visit<'a> for BindingFinder<'n> {
+  fn visit_binding_identifier(&mut self, ident: &BindingIdentifier<'a>) {
+    if self.span.is_none() && ident.name.as_str() == self.name {
+      self.span = Some(ident.span);
+    }
+  }
+}
+
+/// The module already imports the template binding, i.e. from an earlier replacement.
+fn has_template_import(ctx: &TransformContext, import: &TemplateImport) -> bool {
+  ctx.program().body.iter().any(|stmt| {
+    let Statement::ImportDeclaration(decl) = stmt else {
+      return false;
+    };
+
+    decl.source.value.as_str() == import.module_name
+      && decl.specifiers.iter().flatten().any(|specifier| {
+        matches!(
+          specifier,
+          ImportDeclarationSpecifier::ImportSpecifier(specifier)
+            if specifier.imported.name().as_str() == import.imported
+              && specifier.local.name.as_str() == import.local
+        )
+      })
+  })
+}
+
+/// Bindings are only removed when replaced calls were their last users, i.e. `makeStyles` import.
+/// Imports with specifiers & declarations without side effects are kept only when referenced.
+fn is_kept_statement(stmt: &Statement, side_effects: &SideEffects) -> bool {
+  match stmt {
+    Statement::ImportDeclaration(_) => bare_import_source(stmt).is_some(),
+    Statement::VariableDeclaration(_)
+    | Statement::FunctionDeclaration(_)
+    | Statement::ClassDeclaration(_) => side_effects.statement_may_have_side_effects(stmt),
+    _ => true,
+  }
+}
+
+/// Local names of imports that are referenced in the program.
+fn referenced_imports(ctx: &TransformContext) -> HashSet<String> {
+  let program = ctx.program();
+  let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+    .build(&program)
+    .semantic;
+  let symbols = semantic.symbols();
+
+  program
+    .body
+    .iter()
+    .filter_map(|stmt| match stmt {
+      Statement::ImportDeclaration(decl) => decl.specifiers.as_ref(),
+      _ => None,
+    })
+    .flatten()
+    .filter(|specifier| {
+      specifier
+        .local()
+        .symbol_id
+        .get()
+        .is_some_and(|symbol_id| !symbols.get_resolved_reference_ids(symbol_id).is_empty())
+    })
+    .map(|specifier| specifier.local().name.to_string())
+    .collect()
+}
+
+/// Removes import specifiers that were referenced only by replaced calls, imports that are left
+/// without specifiers are removed as a whole.
+fn prune_import_specifiers(ctx: &TransformContext, referenced_before: &HashSet<String>) {
+  let referenced_after = referenced_imports(ctx);
+  let is_unused = |name: &str| referenced_before.contains(name) && !referenced_after.contains(name);
+
+  ctx.program_mut().body.retain_mut(|stmt| {
+    let Statement::ImportDeclaration(decl) = stmt else {
+      return true;
+    };
+    let Some(specifiers) = &mut decl.specifiers else {
+      return true;
+    };
+    if specifiers.is_empty() {
+      return true;
+    }
+
+    specifiers.retain(|specifier| !is_unused(specifier.local().name.as_str()));
+    !specifiers.is_empty()
+  });
+}
+
+pub(crate) fn replace_calls_inner<'a>(
+  allocator: &'a Allocator,
+  filename: &'a String,
+  source_text: &'a String,
+  replacements: &[CallReplacement],
+  options: &ReplaceCallsOptions,
+) -> TransformInner {
+  let ctx = TransformContext::new(allocator, filename, source_text);
+  let used_before =
+    find_reachable_statements(&ctx, is_kept_statement, SideEffectsMode::Drop, false);
+  let referenced_before = referenced_imports(&ctx);
+
+  let mut replacer = CallReplacer {
+    replacements: HashMap::new(),
+  };
+  let mut diagnostics = Vec::new();
+
+  for replacement in replacements {
+    let span = Span::new(replacement.start, replacement.end);
+    let code = options
+      .template
+      .replace(PAYLOAD_PLACEHOLDER, &replacement.payload);
+    let code = allocator.alloc_str(&code);
+
+    match Parser::new(allocator, code, SourceType::default()).parse_expression() {
+      Ok(mut expression) => {
+        SpanReplacer { span }.visit_expression(&mut expression);
+        replacer
+          .replacements
+          .insert((span.start, span.end), expression);
+      }
+      Err(errors) => diagnostics.extend(errors.into_iter().map(|error| {
+        error
+          .with_help("The template with the payload is not a valid expression")
+          .with_label(span)
+      })),
+    }
+  }
+
+  let has_replacements = !replacer.replacements.is_empty();
+  replacer.visit_program(&mut ctx.program_mut());
+
+  let mut unmatched: Vec<(u32, u32)> = replacer.replacements.keys().copied().collect();
+  unmatched.sort_unstable();
+  diagnostics.extend(unmatched.into_iter().map(|(start, end)| {
+    OxcDiagnostic::error("No call expression found to replace").with_label(Span::new(start, end))
+  }));
+  ctx.add_diagnostics(diagnostics);
+
+  let used_after = find_reachable_statements(&ctx, is_kept_statement, SideEffectsMode::Drop, false);
+  let should_keep: Vec<bool> = used_before
+    .iter()
+    .zip(&used_after)
+    .map(|(before, after)| !before || *after)
+    .collect();
+
+  retain_statements(&ctx, &should_keep);
+  prune_import_specifiers(&ctx, &referenced_before);
+
+  if has_replacements {
+    let mut imports = AstBuilder::new(allocator).vec();
+    let mut diagnostics = Vec::new();
+
+    for import in options.imports.iter().flatten() {
+      if has_template_import(&ctx, import) {
+        continue;
+      }
+
+      let mut finder = BindingFinder {
+        name: &import.local,
+        span: None,
+      };
+      finder.visit_program(&ctx.program());
+
+      if let Some(span) = finder.span {
+        diagnostics.push(
+          OxcDiagnostic::error(format!(
+            "`{}` is already declared, the template import can't be added",
+            import.local
+          ))
+          .with_help("Rename the binding or change the local name of the template import")
+          .with_label(span),
+        );
+        continue;
+      }
+
+      let module_name =
+        serde_json::to_string(&import.module_name).expect("a string is always serializable");
+      let code = format!(
+        "import {{ {} as {} }} from {};",
+        import.imported, import.local, module_name
+      );
+      let parsed = Parser::new(allocator, allocator.alloc_str(&code), SourceType::mjs()).parse();
+
+      // offsets of errors point to the import code, not to the source text
+      if !parsed.errors.is_empty() {
+        let errors: Vec<String> = parsed.errors.iter().map(ToString::to_string).collect();
+
+        diagnostics.push(
+          OxcDiagnostic::error(format!("The template import `{code}` is not valid"))
+            .with_help(errors.join("\n")),
+        );
+        continue;
+      }
+
+      let mut program = parsed.program;
+      ImportSpanResetter.visit_program(&mut program);
+      imports.extend(program.body);
+    }
+
+    ctx.add_diagnostics(diagnostics);
+
+    let mut program = ctx.program_mut();
+    let body = std::mem::replace(&mut program.body, imports);
+
+    program.body.extend(body);
+  }
+
+  pass_to_output(&ctx, None, options.sourcemap.unwrap_or_default(), false)
+}
+
+/// Replaces calls with precomputed results, i.e. `makeStyles({ ... })` with
+/// `__styles({ "root": "f3j2jz" })`. Imports & declarations that were used only by the replaced
+/// calls are removed.
+///
+/// @param filename The name of the file being transformed.
+/// @param sourceText the source code itself
+/// @param replacements Spans of calls in the source text & their payloads.
+/// @param options The template of replacements & imports it needs.
+///
+/// @returns the code, a source map when requested and errors for replacements that could not be
+/// applied.
+#[napi]
+pub fn replace_calls(
+  filename: String,
+  source_text: String,
+  replacements: Vec<CallReplacement>,
+  options: ReplaceCallsOptions,
+) -> TransformInner {
+  let allocator = Allocator::default();
+
+  replace_calls_inner(&allocator, &filename, &source_text, &replacements, &options)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::replace_calls::{replace_calls, CallReplacement, ReplaceCallsOptions, TemplateImport};
+  use crate::transformer::TransformInner;
+
+  pub fn replace_styles_calls(input: &str, replacements: &[(&str, &str)]) -> TransformInner {
+    let replacements = replacements
+      .iter()
+      .map(|(call, payload)| {
+        let start = input.find(call).expect("call should be in the input");
+
+        CallReplacement {
+          start: start as u32,
+          end: (start + call.len()) as u32,
+          payload: payload.to_string(),
+        }
+      })
+      .collect();
+
+    replace_calls(
+      "source.js".to_string(),
+      input.to_string(),
+      replacements,
+      ReplaceCallsOptions {
+        template: "_styles(<json>)".to_string(),
+        imports: Some(vec![TemplateImport {
+          module_name: "@griffel/core".to_string(),
+          imported: "__styles".to_string(),
+          local: "_styles".to_string(),
+        }]),
+        sourcemap: Some(true),
+      },
+    )
+  }
+}
+
+#[test]
+fn test_replace_calls() {
+  let input = r#"
+    import { makeStyles, mergeClasses } from "@griffel/core";
+    import { tokens } from "./tokens";
+    import { unused } from "./unused";
+
+    const root = { color: tokens.colorBrand };
+    export const useStyles = makeStyles({ root });
+    export const useClasses = () => mergeClasses(useStyles().root);
+  "#;
+  let output = r#"
+    import { __styles as _styles } from "@griffel/core";
+    import { mergeClasses } from "@griffel/core";
+    import { unused } from "./unused";
+    export const useStyles = _styles({ "root": "fe3e8s9" });
+    export const useClasses = () => mergeClasses(useStyles().root);
+  "#;
+
+  let result = tests::replace_styles_calls(
+    input,
+    &[("makeStyles({ root })", r#"{ "root": "fe3e8s9" }"#)],
+  );
+  let result_code = result.output.replace("\t", "  ");
+
+  assert_eq!(result.errors.len(), 0);
+  assert!(result.map.is_some());
+  assert_eq!(result_code.trim(), textwrap::dedent(output).trim());
+}
+
+#[test]
+fn test_replace_calls_errors() {
+  let input = r#"export const useStyles = makeStyles({});"#;
+
+  let result = tests::replace_styles_calls(
+    input,
+    &[("makeStyles({})", "{ invalid"), ("useStyles", "{}")],
+  );
+
+  assert_eq!(result.errors.len(), 2);
+  assert!(result.errors[0].contains("The template with the payload is not a valid expression"));
+  assert!(result.errors[1].contains("No call expression found to replace"));
+  assert!(result.output.contains("makeStyles({})"));
+  assert!(!result.output.contains("_styles"));
+}
+
+#[test]
+fn test_replace_calls_existing_import() {
+  let input = r#"
+    import { __styles as _styles } from "@griffel/core";
+    import { makeStyles } from "@griffel/core";
+    export const useStyles = makeStyles({});
+    export const useOtherStyles = _styles({});
+  "#;
+
+  let result = tests::replace_styles_calls(input, &[("makeStyles({})", "{}")]);
+
+  assert_eq!(result.errors.len(), 0);
+  assert_eq!(result.output.matches("__styles as _styles").count(), 1);
+}
+
+#[test]
+fn test_replace_calls_import_conflict() {
+  let input = r#"
+    import { makeStyles } from "@griffel/core";
+    export const _styles = "root";
+    export const useStyles = makeStyles({});
+  "#;
+
+  let result = tests::replace_styles_calls(input, &[("makeStyles({})", "{}")]);
+
+  assert_eq!(result.errors.len(), 1);
+  assert!(result.errors[0].contains("`_styles` is already declared"));
+  assert!(!result.output.contains("__styles"));
+}
+
+#[test]
+fn test_replace_calls_invalid_import() {
+  let input = r#"export const useStyles = makeStyles({});"#;
+  let start = input.find("makeStyles").unwrap() as u32;
+
+  let result = replace_calls(
+    "source.js".to_string(),
+    input.to_string(),
+    vec![CallReplacement {
+      start,
+      end: start + "makeStyles({})".len() as u32,
+      payload: "{}".to_string(),
+    }],
+    ReplaceCallsOptions {
+      template: "styles(<json>)".to_string(),
+      imports: Some(vec![TemplateImport {
+        module_name: "@griffel/core".to_string(),
+        imported: "__styles".to_string(),
+        local: "not valid".to_string(),
+      }]),
+      sourcemap: None,
+    },
+  );
+
+  assert_eq!(result.errors.len(), 1);
+  assert!(result.errors[0].contains("The template import"));
+  assert!(result.errors[0].contains("is not valid"));
+  assert!(result.output.contains("styles({})"));
+}
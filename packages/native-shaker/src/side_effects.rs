@@ -0,0 +1,482 @@
+use oxc_ast::ast::{
+  Argument, ArrayExpressionElement, Class, ClassElement, Declaration, Expression, Function,
+  ObjectPropertyKind, Program, PropertyKey, Statement, UnaryOperator, VariableDeclaration,
+};
+use oxc_ast::Trivias;
+use oxc_semantic::{Semantic, SymbolFlags, SymbolId, SymbolTable};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Annotation {
+  /// `/*#__PURE__*/`, the annotated call or `new` expression can be dropped if unused.
+  Pure,
+  /// `/*#__NO_SIDE_EFFECTS__*/`, calls of the annotated function can be dropped if unused.
+  NoSideEffects,
+}
+
+fn parse_annotation(comment: &str) -> Option<Annotation> {
+  let comment = comment
+    .trim_start_matches("//")
+    .trim_start_matches("/*")
+    .trim_end_matches("*/")
+    .trim();
+  let name = comment
+    .strip_prefix('#')
+    .or_else(|| comment.strip_prefix('@'))?;
+
+  match name {
+    "__PURE__" => Some(Annotation::Pure),
+    "__NO_SIDE_EFFECTS__" => Some(Annotation::NoSideEffects),
+    _ => None,
+  }
+}
+
+/// Maps positions of annotated code to annotations. The position is the start of the first token
+/// after the comment, `export` & `default` are skipped, so `NO_SIDE_EFFECTS` annotations point to
+/// declarations even after exports were expanded.
+fn collect_annotations(source_text: &str, trivias: &Trivias) -> HashMap<u32, Annotation> {
+  trivias
+    .comments()
+    .filter_map(|comment| {
+      let annotation = parse_annotation(comment.span.source_text(source_text))?;
+
+      let mut rest = source_text[comment.span.end as usize..]
+        .trim_start_matches("*/")
+        .trim_start();
+      if annotation == Annotation::NoSideEffects {
+        for keyword in ["export", "default"] {
+          if let Some(stripped) = rest.strip_prefix(keyword) {
+            rest = stripped.trim_start();
+          }
+        }
+      }
+
+      let position = (source_text.len() - rest.len()) as u32;
+      Some((position, annotation))
+    })
+    .collect()
+}
+
+/// Checks if statements & expressions can change the state of the program when evaluated. The
+/// check is conservative: anything that is not known to be pure is considered to have side
+/// effects.
+pub(crate) struct SideEffects<'a> {
+  symbols: &'a SymbolTable,
+  annotations: HashMap<u32, Annotation>,
+  /// Top-level functions annotated with `NO_SIDE_EFFECTS`.
+  pure_functions: HashSet<SymbolId>,
+  /// Reads of properties of objects that are not declared in the module can run getters.
+  property_read_side_effects: bool,
+}
+
+impl<'a> SideEffects<'a> {
+  pub fn new(
+    source_text: &str,
+    trivias: &Trivias,
+    semantic: &'a Semantic,
+    program: &Program,
+  ) -> Self {
+    let mut side_effects = Self {
+      symbols: semantic.symbols(),
+      annotations: collect_annotations(source_text, trivias),
+      pure_functions: HashSet::new(),
+      property_read_side_effects: false,
+    };
+
+    for stmt in &program.body {
+      match stmt {
+        Statement::FunctionDeclaration(function) => side_effects.collect_pure_function(function),
+        Statement::VariableDeclaration(decl) => side_effects.collect_pure_variables(decl),
+        Statement::ExportNamedDeclaration(export) => match &export.declaration {
+          Some(Declaration::FunctionDeclaration(function)) => {
+            side_effects.collect_pure_function(function)
+          }
+          Some(Declaration::VariableDeclaration(decl)) => side_effects.collect_pure_variables(decl),
+          _ => {}
+        },
+        _ => {}
+      }
+    }
+
+    side_effects
+  }
+
+  /// Considers reads of properties of imports & globals to have side effects, their getters are
+  /// not known.
+  pub fn with_property_read_side_effects(mut self, property_read_side_effects: bool) -> Self {
+    self.property_read_side_effects = property_read_side_effects;
+    self
+  }
+
+  fn has_annotation(&self, position: u32, annotation: Annotation) -> bool {
+    self.annotations.get(&position) == Some(&annotation)
+  }
+
+  fn collect_pure_function(&mut self, function: &Function) {
+    if !self.has_annotation(function.span.start, Annotation::NoSideEffects) {
+      return;
+    }
+
+    if let Some(ident) = &function.id {
+      self.pure_functions.extend(ident.symbol_id.get());
+    }
+  }
+
+  /// Handles `/*#__NO_SIDE_EFFECTS__*/ const a = () => {}`.
+  fn collect_pure_variables(&mut self, decl: &VariableDeclaration) {
+    if !self.has_annotation(decl.span.start, Annotation::NoSideEffects) {
+      return;
+    }
+
+    for declarator in &decl.declarations {
+      let is_function = matches!(
+        declarator.init,
+        Some(Expression::FunctionExpression(_) | Expression::ArrowFunctionExpression(_))
+      );
+
+      if let (true, Some(ident)) = (is_function, declarator.id.get_binding_identifier()) {
+        self.pure_functions.extend(ident.symbol_id.get());
+      }
+    }
+  }
+
+  pub fn statement_may_have_side_effects(&self, stmt: &Statement) -> bool {
+    match stmt {
+      Statement::EmptyStatement(_)
+      | Statement::FunctionDeclaration(_)
+      | Statement::ImportDeclaration(_)
+      | Statement::ExportAllDeclaration(_)
+      | Statement::TSTypeAliasDeclaration(_)
+      | Statement::TSInterfaceDeclaration(_)
+      | Statement::TSEnumDeclaration(_)
+      | Statement::TSModuleDeclaration(_)
+      | Statement::TSImportEqualsDeclaration(_) => false,
+      Statement::ExpressionStatement(stmt) => {
+        self.expression_may_have_side_effects(&stmt.expression)
+      }
+      Statement::VariableDeclaration(decl) => self.variable_declaration_may_have_side_effects(decl),
+      Statement::ClassDeclaration(class) => self.class_may_have_side_effects(class),
+      Statement::ExportNamedDeclaration(export) => match &export.declaration {
+        Some(Declaration::VariableDeclaration(decl)) => {
+          self.variable_declaration_may_have_side_effects(decl)
+        }
+        Some(Declaration::ClassDeclaration(class)) => self.class_may_have_side_effects(class),
+        _ => false,
+      },
+      Statement::ExportDefaultDeclaration(_) => false,
+      _ => true,
+    }
+  }
+
+  fn variable_declaration_may_have_side_effects(&self, decl: &VariableDeclaration) -> bool {
+    decl.declarations.iter().any(|declarator| {
+      declarator
+        .init
+        .as_ref()
+        .is_some_and(|init| self.expression_may_have_side_effects(init))
+    })
+  }
+
+  fn class_may_have_side_effects(&self, class: &Class) -> bool {
+    if !class.decorators.is_empty() || class.super_class.is_some() {
+      return true;
+    }
+
+    class.body.body.iter().any(|element| match element {
+      ClassElement::StaticBlock(_) => true,
+      ClassElement::MethodDefinition(method) => {
+        method.computed && self.property_key_may_have_side_effects(&method.key)
+      }
+      ClassElement::PropertyDefinition(property) => {
+        (property.computed && self.property_key_may_have_side_effects(&property.key))
+          || (property.r#static
+            && property
+              .value
+              .as_ref()
+              .is_some_and(|value| self.expression_may_have_side_effects(value)))
+      }
+      ClassElement::AccessorProperty(_) | ClassElement::TSIndexSignature(_) => false,
+    })
+  }
+
+  fn property_key_may_have_side_effects(&self, key: &PropertyKey) -> bool {
+    key
+      .as_expression()
+      .is_some_and(|expr| self.expression_may_have_side_effects(expr))
+  }
+
+  fn arguments_may_have_side_effects(&self, arguments: &[Argument]) -> bool {
+    arguments.iter().any(|argument| match argument {
+      Argument::SpreadElement(spread) => self.expression_may_have_side_effects(&spread.argument),
+      argument => argument
+        .as_expression()
+        .is_some_and(|expr| self.expression_may_have_side_effects(expr)),
+    })
+  }
+
+  /// Checks if a callee is a top-level function annotated with `NO_SIDE_EFFECTS`.
+  fn is_pure_function(&self, callee: &Expression) -> bool {
+    let Expression::Identifier(ident) = callee.get_inner_expression() else {
+      return false;
+    };
+
+    ident
+      .reference_id
+      .get()
+      .and_then(|reference_id| self.symbols.get_reference(reference_id).symbol_id())
+      .is_some_and(|symbol_id| self.pure_functions.contains(&symbol_id))
+  }
+
+  /// Checks if an object is declared in the module, i.e. `theme` of `theme.colors.brand` when
+  /// `theme` is a local binding. Imports, globals & results of calls are not.
+  fn is_local_object(&self, object: &Expression) -> bool {
+    match object.get_inner_expression() {
+      Expression::ObjectExpression(_) | Expression::ArrayExpression(_) => true,
+      Expression::StaticMemberExpression(member) => self.is_local_object(&member.object),
+      Expression::ComputedMemberExpression(member) => self.is_local_object(&member.object),
+      Expression::Identifier(ident) => ident
+        .reference_id
+        .get()
+        .and_then(|reference_id| self.symbols.get_reference(reference_id).symbol_id())
+        .is_some_and(|symbol_id| {
+          !self
+            .symbols
+            .get_flags(symbol_id)
+            .intersects(SymbolFlags::Import)
+        }),
+      _ => false,
+    }
+  }
+
+  /// Reads of properties of objects that are not declared in the module run unknown getters, they
+  /// are considered pure unless [`Self::with_property_read_side_effects`] is enabled.
+  fn property_read_may_have_side_effects(&self, object: &Expression) -> bool {
+    (self.property_read_side_effects && !self.is_local_object(object))
+      || self.expression_may_have_side_effects(object)
+  }
+
+  /// Reads of identifiers are considered pure, reads of members too unless
+  /// [`Self::with_property_read_side_effects`] is enabled.
+  pub fn expression_may_have_side_effects(&self, expr: &Expression) -> bool {
+    match expr {
+      Expression::BooleanLiteral(_)
+      | Expression::NullLiteral(_)
+      | Expression::NumericLiteral(_)
+      | Expression::BigIntLiteral(_)
+      | Expression::RegExpLiteral(_)
+      | Expression::StringLiteral(_)
+      | Expression::Identifier(_)
+      | Expression::ThisExpression(_)
+      | Expression::MetaProperty(_)
+      | Expression::FunctionExpression(_)
+      | Expression::ArrowFunctionExpression(_) => false,
+      Expression::TemplateLiteral(template) => template
+        .expressions
+        .iter()
+        .any(|expr| self.expression_may_have_side_effects(expr)),
+      Expression::ArrayExpression(array) => array.elements.iter().any(|element| match element {
+        ArrayExpressionElement::SpreadElement(spread) => {
+          self.expression_may_have_side_effects(&spread.argument)
+        }
+        ArrayExpressionElement::Elision(_) => false,
+        element => element
+          .as_expression()
+          .is_some_and(|expr| self.expression_may_have_side_effects(expr)),
+      }),
+      Expression::ObjectExpression(object) => {
+        object.properties.iter().any(|property| match property {
+          ObjectPropertyKind::ObjectProperty(property) => {
+            (property.computed && self.property_key_may_have_side_effects(&property.key))
+              || self.expression_may_have_side_effects(&property.value)
+          }
+          ObjectPropertyKind::SpreadProperty(spread) => {
+            self.expression_may_have_side_effects(&spread.argument)
+          }
+        })
+      }
+      Expression::ClassExpression(class) => self.class_may_have_side_effects(class),
+      // Arguments of annotated calls are still evaluated, same as bundlers do
+      Expression::CallExpression(call)
+        if self.has_annotation(call.span.start, Annotation::Pure)
+          || self.is_pure_function(&call.callee) =>
+      {
+        self.arguments_may_have_side_effects(&call.arguments)
+      }
+      Expression::NewExpression(new) if self.has_annotation(new.span.start, Annotation::Pure) => {
+        self.arguments_may_have_side_effects(&new.arguments)
+      }
+      Expression::UnaryExpression(unary) => {
+        unary.operator == UnaryOperator::Delete
+          || self.expression_may_have_side_effects(&unary.argument)
+      }
+      Expression::BinaryExpression(binary) => {
+        self.expression_may_have_side_effects(&binary.left)
+          || self.expression_may_have_side_effects(&binary.right)
+      }
+      Expression::LogicalExpression(logical) => {
+        self.expression_may_have_side_effects(&logical.left)
+          || self.expression_may_have_side_effects(&logical.right)
+      }
+      Expression::ConditionalExpression(conditional) => {
+        self.expression_may_have_side_effects(&conditional.test)
+          || self.expression_may_have_side_effects(&conditional.consequent)
+          || self.expression_may_have_side_effects(&conditional.alternate)
+      }
+      Expression::SequenceExpression(sequence) => sequence
+        .expressions
+        .iter()
+        .any(|expr| self.expression_may_have_side_effects(expr)),
+      Expression::ParenthesizedExpression(parenthesized) => {
+        self.expression_may_have_side_effects(&parenthesized.expression)
+      }
+      Expression::StaticMemberExpression(member) => {
+        self.property_read_may_have_side_effects(&member.object)
+      }
+      Expression::ComputedMemberExpression(member) => {
+        self.property_read_may_have_side_effects(&member.object)
+          || self.expression_may_have_side_effects(&member.expression)
+      }
+      Expression::PrivateFieldExpression(member) => {
+        self.expression_may_have_side_effects(&member.object)
+      }
+      Expression::TSAsExpression(_)
+      | Expression::TSSatisfiesExpression(_)
+      | Expression::TSNonNullExpression(_)
+      | Expression::TSTypeAssertion(_)
+      | Expression::TSInstantiationExpression(_) => {
+        self.expression_may_have_side_effects(expr.get_inner_expression())
+      }
+      // Calls, assignments, updates, `await`, `yield`, tagged templates, imports, etc.
+      _ => true,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::context::TransformContext;
+  use crate::side_effects::SideEffects;
+  use oxc_allocator::Allocator;
+  use oxc_semantic::SemanticBuilder;
+
+  /// Checks the last statement of the source, previous ones can declare bindings used by it.
+  pub fn assert_side_effects(source_text: &str, expected: bool) {
+    assert_side_effects_with_options(source_text, false, expected);
+  }
+
+  pub fn assert_side_effects_with_options(
+    source_text: &str,
+    property_read_side_effects: bool,
+    expected: bool,
+  ) {
+    let allocator = Allocator::default();
+    let ctx = TransformContext::new(&allocator, "source.ts", source_text);
+    let program = ctx.program();
+    let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+      .build(&program)
+      .semantic;
+    let side_effects = SideEffects::new(ctx.source_text(), &ctx.trivias, &semantic, &program)
+      .with_property_read_side_effects(property_read_side_effects);
+
+    assert_eq!(
+      side_effects.statement_may_have_side_effects(program.body.last().unwrap()),
+      expected,
+      "{source_text}"
+    );
+  }
+}
+
+#[test]
+
+fn test_pure_statements() {
+  tests::assert_side_effects("const a = 1, b = 'b', c = `c${a}`;", false);
+  tests::assert_side_effects("const a = { b: [1, ...c], ...d, [e]: () => {} };", false);
+  tests::assert_side_effects("const a = b.c ? d[e] : !f;", false);
+  tests::assert_side_effects("const a = b as string;", false);
+  tests::assert_side_effects("function a() { b(); }", false);
+  tests::assert_side_effects("class A { b = c(); d() {} }", false);
+  tests::assert_side_effects("export const a = 1;", false);
+  tests::assert_side_effects("import { a } from 'a';", false);
+}
+
+#[test]
+
+fn test_statements_with_side_effects() {
+  tests::assert_side_effects("a();", true);
+  tests::assert_side_effects("const a = create();", true);
+  tests::assert_side_effects("const a = new A();", true);
+  tests::assert_side_effects("a.b = 1;", true);
+  tests::assert_side_effects("a++;", true);
+  tests::assert_side_effects("delete a.b;", true);
+  tests::assert_side_effects("const a = { b: c() };", true);
+  tests::assert_side_effects("class A { static b = c(); }", true);
+  tests::assert_side_effects("class A { static { b(); } }", true);
+  tests::assert_side_effects("class A extends B {}", true);
+  tests::assert_side_effects("export const a = b`c`;", true);
+  tests::assert_side_effects("if (a) { b(); }", true);
+}
+
+#[test]
+
+fn test_pure_annotations() {
+  tests::assert_side_effects("const a = /*#__PURE__*/ create();", false);
+  tests::assert_side_effects("const a = /* @__PURE__ */ new A();", false);
+  tests::assert_side_effects("/*#__PURE__*/ a.b(c, 'd');", false);
+  tests::assert_side_effects("const a = /*#__PURE__*/ create(b());", true);
+  tests::assert_side_effects("const a = create(/*#__PURE__*/ b());", true);
+  tests::assert_side_effects("const a = /* PURE */ create();", true);
+}
+
+#[test]
+
+fn test_no_side_effects_annotations() {
+  tests::assert_side_effects(
+    "/*#__NO_SIDE_EFFECTS__*/ function a() {} const b = a(1);",
+    false,
+  );
+  tests::assert_side_effects(
+    "/*#__NO_SIDE_EFFECTS__*/ export function a() {} const b = a(1);",
+    false,
+  );
+  tests::assert_side_effects(
+    "export /*#__NO_SIDE_EFFECTS__*/ function a() {} const b = a(1);",
+    false,
+  );
+  tests::assert_side_effects(
+    "/*#__NO_SIDE_EFFECTS__*/ const a = () => {}; const b = a(1);",
+    false,
+  );
+  tests::assert_side_effects(
+    "/*#__NO_SIDE_EFFECTS__*/ function a() {} const b = a(c());",
+    true,
+  );
+  tests::assert_side_effects("function a() {} const b = a(1);", true);
+}
+
+#[test]
+
+fn test_property_read_side_effects() {
+  // a getter of an imported object can run arbitrary code
+  tests::assert_side_effects_with_options(
+    "import { theme } from './theme'; const a = theme.color;",
+    false,
+    false,
+  );
+  tests::assert_side_effects_with_options(
+    "import { theme } from './theme'; const a = theme.color;",
+    true,
+    true,
+  );
+  tests::assert_side_effects_with_options("const a = window.innerWidth;", true, true);
+  tests::assert_side_effects_with_options("const a = create().b;", true, true);
+  tests::assert_side_effects_with_options(
+    "const theme = { color: 'red' }; const a = theme.color;",
+    true,
+    false,
+  );
+  tests::assert_side_effects_with_options(
+    "const theme = { colors: { brand: 'red' } }; const a = theme.colors['brand'];",
+    true,
+    false,
+  );
+}
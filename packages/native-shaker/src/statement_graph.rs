@@ -0,0 +1,144 @@
+use oxc_ast::ast::Program;
+use oxc_ast::AstKind;
+use oxc_semantic::{AstNodeId, AstNodes, Semantic};
+use std::collections::HashMap;
+
+/// Dependencies between top-level statements of a program. Statements are identified by their
+/// index in `Program::body`.
+pub(crate) struct StatementGraph {
+  /// Maps nodes that are direct children of the program to statement indexes.
+  statement_indexes: HashMap<AstNodeId, usize>,
+  /// For each statement, indexes of statements that declare root symbols referenced by it.
+  dependencies: Vec<Vec<usize>>,
+}
+
+impl StatementGraph {
+  pub fn new(semantic: &Semantic, program: &Program) -> Self {
+    let nodes = semantic.nodes();
+    let symbols = semantic.symbols();
+    let root_scope_id = semantic.scopes().root_scope_id();
+
+    let statement_indexes: HashMap<AstNodeId, usize> = collect_statement_node_ids(nodes)
+      .into_iter()
+      .enumerate()
+      .map(|(index, node_id)| (node_id, index))
+      .collect();
+
+    debug_assert_eq!(statement_indexes.len(), program.body.len());
+
+    let mut graph = Self {
+      statement_indexes,
+      dependencies: vec![Vec::new(); program.body.len()],
+    };
+
+    // A reference to a symbol from a nested scope can only be inside the statement that declares
+    // it, so only root symbols can create edges between statements
+    for reference in symbols.references.iter() {
+      let Some(symbol_id) = reference.symbol_id() else {
+        continue;
+      };
+
+      if symbols.get_scope_id(symbol_id) != root_scope_id {
+        continue;
+      }
+
+      let from = graph.find_statement_index(nodes, reference.node_id());
+      let to = graph.find_statement_index(nodes, symbols.get_declaration(symbol_id));
+
+      if let (Some(from), Some(to)) = (from, to) {
+        if from != to {
+          graph.dependencies[from].push(to);
+        }
+      }
+    }
+
+    for dependencies in &mut graph.dependencies {
+      dependencies.sort_unstable();
+      dependencies.dedup();
+    }
+
+    graph
+  }
+
+  pub fn len(&self) -> usize {
+    self.dependencies.len()
+  }
+
+  /// Returns the index of the top-level statement that contains a node.
+  pub fn find_statement_index(&self, nodes: &AstNodes, node_id: AstNodeId) -> Option<usize> {
+    std::iter::once(node_id)
+      .chain(nodes.ancestors(node_id))
+      .find_map(|id| self.statement_indexes.get(&id).copied())
+  }
+
+  pub fn dependencies(&self, index: usize) -> &[usize] {
+    &self.dependencies[index]
+  }
+
+  /// Marks statements that are transitively required by `roots`.
+  pub fn reachable_from(&self, roots: impl IntoIterator<Item = usize>) -> Vec<bool> {
+    let mut reachable = vec![false; self.len()];
+    let mut worklist: Vec<usize> = roots.into_iter().collect();
+
+    while let Some(index) = worklist.pop() {
+      if reachable[index] {
+        continue;
+      }
+
+      reachable[index] = true;
+      worklist.extend(
+        self.dependencies[index]
+          .iter()
+          .filter(|dependency| !reachable[**dependency]),
+      );
+    }
+
+    reachable
+  }
+}
+
+/// Returns IDs of nodes that are direct children of the program, in the same order as statements
+/// in `Program::body`.
+fn collect_statement_node_ids(nodes: &AstNodes) -> Vec<AstNodeId> {
+  let root_node_id = nodes.root_node().unwrap().id();
+
+  nodes
+    .iter()
+    .filter(|node| nodes.parent_id(node.id()) == Some(root_node_id))
+    .filter(|node| !matches!(node.kind(), AstKind::Hashbang(_) | AstKind::Directive(_)))
+    .map(|node| node.id())
+    .collect()
+}
+
+#[test]
+fn test_reachable_from() {
+  use oxc_allocator::Allocator;
+  use oxc_semantic::SemanticBuilder;
+
+  use crate::context::TransformContext;
+
+  let source_text = r#"
+    const a = 1;
+    const b = a;
+    function c() { return d(); }
+    function d() { return c() + b; }
+    const e = 2;
+  "#;
+
+  let allocator = Allocator::default();
+  let ctx = TransformContext::new(&allocator, "source.js", source_text);
+  let program = ctx.program();
+  let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+    .build(&program)
+    .semantic;
+
+  let graph = StatementGraph::new(&semantic, &program);
+
+  assert_eq!(graph.dependencies(1), &[0]);
+  assert_eq!(graph.dependencies(2), &[3]);
+  assert_eq!(graph.dependencies(3), &[1, 2]);
+  assert_eq!(
+    graph.reachable_from([2]),
+    vec![true, true, true, true, false]
+  );
+}
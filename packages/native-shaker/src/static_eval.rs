@@ -0,0 +1,984 @@
+use crate::context::TransformContext;
+use crate::crawler::shared_resolver;
+use crate::export_expand::number_to_js_string;
+use crate::module_resolve::{resolve, ModuleResolver};
+use crate::side_effects::SideEffects;
+use crate::statement_graph::StatementGraph;
+use crate::transformer::pass_to_align_exports;
+use napi_derive::napi;
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+  ArrayExpressionElement, BinaryOperator, BindingIdentifier, BindingPatternKind, Expression,
+  ImportDeclarationSpecifier, ObjectPropertyKind, Program, PropertyKind, Statement, UnaryOperator,
+  VariableDeclarationKind,
+};
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_resolver::Resolver;
+use oxc_semantic::SemanticBuilder;
+use oxc_span::{GetSpan, Span};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Statically known exports of a module.
+#[napi(object)]
+pub struct StaticEvaluation {
+  /// Exports that are statically known, serialized to a JSON object.
+  pub values: String,
+  /// Requested exports that are not statically known, they have to be evaluated at runtime.
+  pub unknown: Vec<String>,
+  /// Explains why exports in `unknown` could not be evaluated.
+  pub diagnostics: Vec<String>,
+}
+
+/// A statically known value, objects keep their keys in the order that JS enumerates them.
+#[derive(Clone)]
+enum JsValue {
+  Null,
+  Bool(bool),
+  Number(f64),
+  String(String),
+  Array(Vec<JsValue>),
+  Object(Vec<(String, JsValue)>),
+}
+
+impl JsValue {
+  fn as_f64(&self) -> Option<f64> {
+    match self {
+      JsValue::Number(value) => Some(*value),
+      _ => None,
+    }
+  }
+
+  fn write_json(&self, out: &mut String) {
+    match self {
+      JsValue::Null => out.push_str("null"),
+      JsValue::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+      JsValue::Number(value) => out.push_str(&number_to_json(*value)),
+      JsValue::String(value) => out.push_str(&json_string(value)),
+      JsValue::Array(items) => {
+        out.push('[');
+        for (index, item) in items.iter().enumerate() {
+          if index > 0 {
+            out.push(',');
+          }
+          item.write_json(out);
+        }
+        out.push(']');
+      }
+      JsValue::Object(entries) => {
+        out.push('{');
+        for (index, (key, value)) in entries.iter().enumerate() {
+          if index > 0 {
+            out.push(',');
+          }
+          out.push_str(&json_string(key));
+          out.push(':');
+          value.write_json(out);
+        }
+        out.push('}');
+      }
+    }
+  }
+
+  fn to_json(&self) -> String {
+    let mut out = String::new();
+    self.write_json(&mut out);
+    out
+  }
+}
+
+fn json_string(value: &str) -> String {
+  serde_json::to_string(value).expect("strings are always serializable")
+}
+
+fn number_to_json(value: f64) -> String {
+  if value.fract() == 0.0 && value.abs() < 9007199254740992.0 {
+    return format!("{}", value as i64);
+  }
+
+  serde_json::Number::from_f64(value)
+    .map_or_else(|| "null".to_string(), |number| number.to_string())
+}
+
+/// Returns the index of a key that JS treats as an array index, they are enumerated first.
+fn array_index(key: &str) -> Option<u32> {
+  key
+    .parse::<u32>()
+    .ok()
+    .filter(|index| *index != u32::MAX && index.to_string() == key)
+}
+
+/// Sets a property of an object the way JS does: existing keys keep their position, array indexes
+/// are ordered ascending before other keys, and other keys are ordered by insertion.
+fn set_property(entries: &mut Vec<(String, JsValue)>, key: String, value: JsValue) {
+  if let Some(entry) = entries.iter_mut().find(|(existing, _)| *existing == key) {
+    entry.1 = value;
+    return;
+  }
+
+  match array_index(&key) {
+    Some(index) => {
+      let position = entries
+        .iter()
+        .position(|(existing, _)| array_index(existing).map_or(true, |other| other > index))
+        .unwrap_or(entries.len());
+      entries.insert(position, (key, value));
+    }
+    None => entries.push((key, value)),
+  }
+}
+
+/// A top-level binding of a module. `ExportReplacer` has already normalized the module, so
+/// exported values are `const` declarations or imports with a single specifier.
+enum Binding {
+  /// A declarator of a `const` declaration, identified by indexes of the statement & declarator.
+  Const {
+    statement: usize,
+    declarator: usize,
+  },
+  Import {
+    source: String,
+    imported: String,
+  },
+  Namespace {
+    source: String,
+  },
+}
+
+/// A parsed module, each module is parsed once per evaluation and shared between its importers.
+struct ModuleScope<'a> {
+  ctx: TransformContext<'a>,
+  bindings: HashMap<String, Binding>,
+  /// Exported name to local name.
+  exports: HashMap<String, String>,
+  /// Exported names in the order of their declaration.
+  export_names: Vec<String>,
+  /// Sources of `export * from "..."`.
+  star_exports: Vec<String>,
+  /// `const` & imported bindings that are reachable by statements with side effects, i.e.
+  /// `s.color = "red"`, mapped to the span of the first such statement.
+  mutations: HashMap<String, Span>,
+  /// Values of bindings that were already evaluated.
+  values: RefCell<HashMap<String, Result<JsValue, String>>>,
+  /// Bindings that are being evaluated, to report circular references.
+  evaluating: RefCell<HashSet<String>>,
+}
+
+impl<'a> ModuleScope<'a> {
+  fn new(ctx: TransformContext<'a>) -> Self {
+    let mut bindings = HashMap::new();
+    let mut exports = HashMap::new();
+    let mut export_names = Vec::new();
+    let mut star_exports = Vec::new();
+    let mut binding_statements = Vec::new();
+
+    let program = ctx.program();
+
+    for (index, stmt) in program.body.iter().enumerate() {
+      match stmt {
+        Statement::VariableDeclaration(decl) if decl.kind == VariableDeclarationKind::Const => {
+          binding_statements.push(index);
+
+          for (declarator_index, declarator) in decl.declarations.iter().enumerate() {
+            if let (BindingPatternKind::BindingIdentifier(ident), Some(_)) =
+              (&declarator.id.kind, &declarator.init)
+            {
+              bindings.insert(
+                ident.name.to_string(),
+                Binding::Const {
+                  statement: index,
+                  declarator: declarator_index,
+                },
+              );
+            }
+          }
+        }
+        Statement::ImportDeclaration(decl) => {
+          binding_statements.push(index);
+          let source = decl.source.value.to_string();
+
+          for specifier in decl.specifiers.iter().flatten() {
+            let binding = match specifier {
+              ImportDeclarationSpecifier::ImportSpecifier(specifier) => Binding::Import {
+                source: source.clone(),
+                imported: specifier.imported.name().to_string(),
+              },
+              ImportDeclarationSpecifier::ImportDefaultSpecifier(_) => Binding::Import {
+                source: source.clone(),
+                imported: "default".to_string(),
+              },
+              ImportDeclarationSpecifier::ImportNamespaceSpecifier(_) => Binding::Namespace {
+                source: source.clone(),
+              },
+            };
+
+            bindings.insert(specifier.local().name.to_string(), binding);
+          }
+        }
+        Statement::ExportNamedDeclaration(decl) if decl.source.is_none() => {
+          for specifier in &decl.specifiers {
+            let exported = specifier.exported.name().to_string();
+
+            if !exports.contains_key(&exported) {
+              export_names.push(exported.clone());
+            }
+            exports.insert(exported, specifier.local.name().to_string());
+          }
+        }
+        Statement::ExportAllDeclaration(decl) if decl.exported.is_none() => {
+          star_exports.push(decl.source.value.to_string());
+        }
+        _ => {}
+      }
+    }
+
+    let mutations = find_mutations(&ctx, &program, &binding_statements);
+    drop(program);
+
+    Self {
+      ctx,
+      bindings,
+      exports,
+      export_names,
+      star_exports,
+      mutations,
+      values: RefCell::new(HashMap::new()),
+      evaluating: RefCell::new(HashSet::new()),
+    }
+  }
+
+  fn path(&self) -> &'a str {
+    self.ctx.file_name()
+  }
+
+  /// Renders a diagnostic with the source of this module.
+  fn report(&self, diagnostic: OxcDiagnostic) -> String {
+    self.ctx.add_diagnostics(vec![diagnostic]);
+    self.ctx.take_and_render_reports().join("\n")
+  }
+
+  fn unknown_expression(&self, expression: &Expression) -> Result<JsValue, String> {
+    Err(self.not_statically_known("The expression", expression.span()))
+  }
+
+  fn not_statically_known(&self, description: &str, span: Span) -> String {
+    self.report(
+      OxcDiagnostic::warn(format!("{description} is not statically known"))
+        .with_help("The value has to be evaluated at runtime")
+        .with_label(span),
+    )
+  }
+}
+
+/// Finds `const` & imported bindings that can be changed by statements with side effects, i.e.
+/// `s.color = "red"` or `register(s)`. Statements also reach bindings through functions they call,
+/// `init()` modifies `s` when `function init() { s.color = "red" }` is declared.
+///
+/// Importers that are not evaluated can still modify exported objects, they are not seen here.
+fn find_mutations(
+  ctx: &TransformContext,
+  program: &Program,
+  binding_statements: &[usize],
+) -> HashMap<String, Span> {
+  let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+    .build(program)
+    .semantic;
+  let graph = StatementGraph::new(&semantic, program);
+  let side_effects = SideEffects::new(ctx.source_text(), &ctx.trivias, &semantic, program);
+
+  let nodes = semantic.nodes();
+  let symbols = semantic.symbols();
+  let mut mutations = HashMap::new();
+
+  // statements that each statement with side effects can run, including itself
+  let reachable_by_side_effects: Vec<(usize, Vec<bool>)> = program
+    .body
+    .iter()
+    .enumerate()
+    .filter(|(_, stmt)| side_effects.statement_may_have_side_effects(stmt))
+    .map(|(index, _)| (index, graph.reachable_from([index])))
+    .collect();
+
+  for index in binding_statements {
+    let idents: Vec<&BindingIdentifier> = match &program.body[*index] {
+      Statement::VariableDeclaration(decl) => decl
+        .declarations
+        .iter()
+        .filter_map(|declarator| match &declarator.id.kind {
+          BindingPatternKind::BindingIdentifier(ident) => Some(&**ident),
+          _ => None,
+        })
+        .collect(),
+      Statement::ImportDeclaration(decl) => decl
+        .specifiers
+        .iter()
+        .flatten()
+        .map(ImportDeclarationSpecifier::local)
+        .collect(),
+      _ => continue,
+    };
+
+    for ident in idents {
+      let Some(symbol_id) = ident.symbol_id.get() else {
+        continue;
+      };
+
+      let referencing_statements: Vec<usize> = symbols
+        .get_resolved_reference_ids(symbol_id)
+        .iter()
+        .filter_map(|reference_id| {
+          graph.find_statement_index(nodes, symbols.get_reference(*reference_id).node_id())
+        })
+        .filter(|statement| statement != index)
+        .collect();
+
+      let mutation = reachable_by_side_effects
+        .iter()
+        .find(|(statement, reachable)| {
+          statement != index
+            && referencing_statements
+              .iter()
+              .any(|referencing| reachable[*referencing])
+        })
+        .map(|(statement, _)| *statement);
+
+      if let Some(statement) = mutation {
+        mutations.insert(ident.name.to_string(), program.body[statement].span());
+      }
+    }
+  }
+
+  mutations
+}
+
+fn number(value: f64) -> Option<JsValue> {
+  value.is_finite().then_some(JsValue::Number(value))
+}
+
+/// Converts a primitive value to a string the same way as the `+` operator does.
+fn to_js_string(value: &JsValue) -> Option<String> {
+  match value {
+    JsValue::String(value) => Some(value.clone()),
+    JsValue::Number(value) => Some(number_to_js_string(*value)),
+    JsValue::Bool(value) => Some(value.to_string()),
+    JsValue::Null => Some("null".to_string()),
+    JsValue::Array(_) | JsValue::Object(_) => None,
+  }
+}
+
+fn read_property(object: &JsValue, key: &str) -> Option<JsValue> {
+  match object {
+    JsValue::Object(entries) => entries
+      .iter()
+      .find(|(existing, _)| existing == key)
+      .map(|(_, value)| value.clone()),
+    JsValue::Array(items) if key == "length" => Some(JsValue::Number(items.len() as f64)),
+    JsValue::Array(items) => key
+      .parse::<usize>()
+      .ok()
+      .and_then(|index| items.get(index).cloned()),
+    JsValue::String(value) if key == "length" => {
+      Some(JsValue::Number(value.encode_utf16().count() as f64))
+    }
+    _ => None,
+  }
+}
+
+/// Folds constant expressions of modules to JSON values, following imports to other modules.
+pub(crate) struct StaticEvaluator<'a> {
+  allocator: &'a Allocator,
+  resolver: Arc<Resolver>,
+  /// Parsed modules keyed by path.
+  modules: HashMap<String, Rc<ModuleScope<'a>>>,
+}
+
+impl<'a> StaticEvaluator<'a> {
+  pub fn new(allocator: &'a Allocator, resolver: Arc<Resolver>) -> Self {
+    Self {
+      allocator,
+      resolver,
+      modules: HashMap::new(),
+    }
+  }
+
+  fn parse_module(&mut self, path: &str, source_text: &str) -> Rc<ModuleScope<'a>> {
+    let path: &'a str = self.allocator.alloc_str(path);
+    let source_text: &'a str = self.allocator.alloc_str(source_text);
+    let module = Rc::new(ModuleScope::new(pass_to_align_exports(
+      self.allocator,
+      path,
+      source_text,
+    )));
+
+    self.modules.insert(path.to_string(), Rc::clone(&module));
+    module
+  }
+
+  /// Returns a module that is read from the disk, it is parsed only the first time.
+  fn load_module(&mut self, path: &str) -> Result<Rc<ModuleScope<'a>>, String> {
+    if let Some(module) = self.modules.get(path) {
+      return Ok(Rc::clone(module));
+    }
+
+    let source_text = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    Ok(self.parse_module(path, &source_text))
+  }
+
+  /// Evaluates requested exports of a module, returns values of known ones serialized to a JSON
+  /// object & diagnostics for unknown ones.
+  pub fn evaluate_module(
+    &mut self,
+    filename: &str,
+    source_text: &str,
+    exports: &[String],
+  ) -> (String, Vec<(String, String)>) {
+    let module = self.parse_module(filename, source_text);
+
+    let mut values = Vec::new();
+    let mut unknown = Vec::new();
+
+    for name in exports {
+      match self.evaluate_export(&module, name) {
+        Ok(value) => set_property(&mut values, name.clone(), value),
+        Err(diagnostic) => unknown.push((name.clone(), diagnostic)),
+      }
+    }
+
+    (JsValue::Object(values).to_json(), unknown)
+  }
+
+  fn resolve_import(&self, module: &ModuleScope, source: &str) -> Result<String, String> {
+    let directory = Path::new(module.path()).parent().unwrap_or(Path::new(""));
+    let resolution = resolve(&self.resolver, directory, source);
+
+    resolution.path.ok_or_else(|| {
+      resolution
+        .error
+        .unwrap_or_else(|| format!("Cannot resolve {source}"))
+    })
+  }
+
+  /// Evaluates an export of a module that is read from the disk.
+  fn evaluate_import(
+    &mut self,
+    module: &ModuleScope,
+    source: &str,
+    name: &str,
+  ) -> Result<JsValue, String> {
+    let path = self.resolve_import(module, source)?;
+    let imported = self.load_module(&path)?;
+
+    self.evaluate_export(&imported, name)
+  }
+
+  fn evaluate_export(&mut self, module: &ModuleScope<'a>, name: &str) -> Result<JsValue, String> {
+    let mut visited = HashSet::new();
+    let mut unresolved = Vec::new();
+
+    self
+      .find_export(module, name, &mut visited, &mut unresolved)
+      .unwrap_or_else(|| {
+        let mut message = format!("{}: `{name}` is not exported", module.path());
+        for error in unresolved {
+          message.push('\n');
+          message.push_str(&error);
+        }
+        Err(message)
+      })
+  }
+
+  /// Evaluates an export of a module or of one of its `export *` sources, the first module that
+  /// provides the name wins. Returns `None` if no module exports the name, star exports that can
+  /// not be resolved are skipped & their errors are collected to `unresolved`.
+  fn find_export(
+    &mut self,
+    module: &ModuleScope<'a>,
+    name: &str,
+    visited: &mut HashSet<String>,
+    unresolved: &mut Vec<String>,
+  ) -> Option<Result<JsValue, String>> {
+    if let Some(local) = module.exports.get(name) {
+      return Some(self.evaluate_binding(module, local, None));
+    }
+    if !visited.insert(module.path().to_string()) {
+      return None;
+    }
+
+    for source in &module.star_exports {
+      let star_module = self
+        .resolve_import(module, source)
+        .and_then(|path| self.load_module(&path));
+
+      match star_module {
+        Ok(star_module) => {
+          if let Some(result) = self.find_export(&star_module, name, visited, unresolved) {
+            return Some(result);
+          }
+        }
+        Err(error) => unresolved.push(error),
+      }
+    }
+
+    None
+  }
+
+  /// Evaluates all exports of a module for `import * as ns`.
+  fn evaluate_namespace(&mut self, module: &ModuleScope, source: &str) -> Result<JsValue, String> {
+    let path = self.resolve_import(module, source)?;
+    let namespace = self.load_module(&path)?;
+
+    let mut values = Vec::new();
+    for name in &namespace.export_names {
+      let value = self.evaluate_export(&namespace, name)?;
+      set_property(&mut values, name.clone(), value);
+    }
+
+    Ok(JsValue::Object(values))
+  }
+
+  fn evaluate_binding(
+    &mut self,
+    module: &ModuleScope<'a>,
+    name: &str,
+    span: Option<Span>,
+  ) -> Result<JsValue, String> {
+    let Some(binding) = module.bindings.get(name) else {
+      let description = format!("`{name}`");
+      return Err(match span {
+        Some(span) => module.not_statically_known(&description, span),
+        None => format!("{}: {description} is not statically known", module.path()),
+      });
+    };
+
+    if let Some(result) = module.values.borrow().get(name) {
+      return result.clone();
+    }
+    if let Some(mutation) = module.mutations.get(name) {
+      let description = format!("`{name}` is modified by this statement and");
+      return Err(module.not_statically_known(&description, *mutation));
+    }
+    if !module.evaluating.borrow_mut().insert(name.to_string()) {
+      let description = format!("`{name}` references itself and");
+      return Err(module.not_statically_known(&description, span.unwrap_or_default()));
+    }
+
+    let result = match binding {
+      Binding::Const {
+        statement,
+        declarator,
+      } => {
+        let program = module.ctx.program();
+        let Statement::VariableDeclaration(decl) = &program.body[*statement] else {
+          unreachable!("`const` bindings are collected from variable declarations");
+        };
+        let init = decl.declarations[*declarator]
+          .init
+          .as_ref()
+          .expect("`const` bindings are collected with initializers");
+
+        self.evaluate_expression(module, init)
+      }
+      Binding::Import { source, imported } => self.evaluate_import(module, source, imported),
+      Binding::Namespace { source } => self.evaluate_namespace(module, source),
+    };
+
+    module.evaluating.borrow_mut().remove(name);
+    module
+      .values
+      .borrow_mut()
+      .insert(name.to_string(), result.clone());
+    result
+  }
+
+  fn evaluate_expression(
+    &mut self,
+    module: &ModuleScope<'a>,
+    expression: &Expression,
+  ) -> Result<JsValue, String> {
+    let expression = expression.get_inner_expression();
+
+    match expression {
+      Expression::NullLiteral(_) => Ok(JsValue::Null),
+      Expression::BooleanLiteral(lit) => Ok(JsValue::Bool(lit.value)),
+      Expression::StringLiteral(lit) => Ok(JsValue::String(lit.value.to_string())),
+      Expression::NumericLiteral(lit) => {
+        number(lit.value).map_or_else(|| module.unknown_expression(expression), Ok)
+      }
+      Expression::TemplateLiteral(template) => {
+        let mut value = String::new();
+
+        for (index, quasi) in template.quasis.iter().enumerate() {
+          let Some(cooked) = &quasi.value.cooked else {
+            return module.unknown_expression(expression);
+          };
+          value.push_str(cooked.as_str());
+
+          if let Some(part) = template.expressions.get(index) {
+            let part = self.evaluate_expression(module, part)?;
+            let Some(part) = to_js_string(&part) else {
+              return module.unknown_expression(expression);
+            };
+            value.push_str(&part);
+          }
+        }
+
+        Ok(JsValue::String(value))
+      }
+      Expression::Identifier(ident) => {
+        self.evaluate_binding(module, ident.name.as_str(), Some(ident.span))
+      }
+      Expression::ArrayExpression(array) => {
+        let mut values = Vec::new();
+
+        for element in &array.elements {
+          match element {
+            ArrayExpressionElement::SpreadElement(spread) => {
+              match self.evaluate_expression(module, &spread.argument)? {
+                JsValue::Array(items) => values.extend(items),
+                _ => return module.unknown_expression(expression),
+              }
+            }
+            ArrayExpressionElement::Elision(_) => values.push(JsValue::Null),
+            element => {
+              let Some(item) = element.as_expression() else {
+                return module.unknown_expression(expression);
+              };
+              values.push(self.evaluate_expression(module, item)?);
+            }
+          }
+        }
+
+        Ok(JsValue::Array(values))
+      }
+      Expression::ObjectExpression(object) => {
+        let mut values = Vec::new();
+
+        for property in &object.properties {
+          match property {
+            ObjectPropertyKind::SpreadProperty(spread) => {
+              match self.evaluate_expression(module, &spread.argument)? {
+                JsValue::Object(entries) => {
+                  for (key, value) in entries {
+                    set_property(&mut values, key, value);
+                  }
+                }
+                JsValue::Null => {}
+                _ => return module.unknown_expression(expression),
+              }
+            }
+            ObjectPropertyKind::ObjectProperty(property) => {
+              if property.kind != PropertyKind::Init || property.method {
+                return module.unknown_expression(expression);
+              }
+
+              let key = match (property.computed, property.key.static_name()) {
+                (false, Some(name)) => name.to_string(),
+                _ => {
+                  let Some(key) = property.key.as_expression() else {
+                    return module.unknown_expression(expression);
+                  };
+                  let key = self.evaluate_expression(module, key)?;
+                  let Some(key) = to_js_string(&key) else {
+                    return module.unknown_expression(expression);
+                  };
+                  key
+                }
+              };
+
+              let value = self.evaluate_expression(module, &property.value)?;
+              set_property(&mut values, key, value);
+            }
+          }
+        }
+
+        Ok(JsValue::Object(values))
+      }
+      Expression::StaticMemberExpression(member) => {
+        // `ns.name` reads a single export instead of the whole module, modified namespaces are
+        // reported by `evaluate_binding`
+        if let Expression::Identifier(ident) = member.object.get_inner_expression() {
+          if let (Some(Binding::Namespace { source }), false) = (
+            module.bindings.get(ident.name.as_str()),
+            module.mutations.contains_key(ident.name.as_str()),
+          ) {
+            return self.evaluate_import(module, source, member.property.name.as_str());
+          }
+        }
+
+        let object = self.evaluate_expression(module, &member.object)?;
+        read_property(&object, member.property.name.as_str())
+          .map_or_else(|| module.unknown_expression(expression), Ok)
+      }
+      Expression::ComputedMemberExpression(member) => {
+        let object = self.evaluate_expression(module, &member.object)?;
+        let key = self.evaluate_expression(module, &member.expression)?;
+
+        to_js_string(&key)
+          .and_then(|key| read_property(&object, &key))
+          .map_or_else(|| module.unknown_expression(expression), Ok)
+      }
+      Expression::UnaryExpression(unary) => {
+        let argument = self.evaluate_expression(module, &unary.argument)?;
+        let value = argument.as_f64().and_then(|value| match unary.operator {
+          UnaryOperator::UnaryNegation => number(-value),
+          UnaryOperator::UnaryPlus => number(value),
+          _ => None,
+        });
+
+        value.map_or_else(|| module.unknown_expression(expression), Ok)
+      }
+      Expression::BinaryExpression(binary) => {
+        let left = self.evaluate_expression(module, &binary.left)?;
+        let right = self.evaluate_expression(module, &binary.right)?;
+        let is_string = |value: &JsValue| matches!(value, JsValue::String(_));
+
+        let value = match (binary.operator, left.as_f64(), right.as_f64()) {
+          (BinaryOperator::Addition, Some(left), Some(right)) => number(left + right),
+          (BinaryOperator::Addition, _, _) if is_string(&left) || is_string(&right) => {
+            to_js_string(&left)
+              .zip(to_js_string(&right))
+              .map(|(left, right)| JsValue::String(left + &right))
+          }
+          (BinaryOperator::Subtraction, Some(left), Some(right)) => number(left - right),
+          (BinaryOperator::Multiplication, Some(left), Some(right)) => number(left * right),
+          (BinaryOperator::Division, Some(left), Some(right)) => number(left / right),
+          (BinaryOperator::Remainder, Some(left), Some(right)) => number(left % right),
+          _ => None,
+        };
+
+        value.map_or_else(|| module.unknown_expression(expression), Ok)
+      }
+      _ => module.unknown_expression(expression),
+    }
+  }
+}
+
+/// Evaluates exports of a module without running it: literals, object & array literals, spreads,
+/// template strings, arithmetic and constants imported from other modules are folded to JSON.
+///
+/// @param filename Path of the module, imports are resolved relative to it.
+/// @param sourceText Code of the module.
+/// @param exports Names of exports to evaluate.
+/// @param resolver A resolver from {@link createResolver}, its cache is shared between calls.
+///
+/// @returns statically known exports and diagnostics for unknown ones.
+#[napi]
+pub fn evaluate_static(
+  filename: String,
+  source_text: String,
+  exports: Vec<String>,
+  resolver: Option<&ModuleResolver>,
+) -> StaticEvaluation {
+  let allocator = Allocator::default();
+  let mut evaluator = StaticEvaluator::new(&allocator, shared_resolver(resolver).resolver());
+  let (values, unknown) = evaluator.evaluate_module(&filename, &source_text, &exports);
+  let (unknown, diagnostics) = unknown.into_iter().unzip();
+
+  StaticEvaluation {
+    values,
+    unknown,
+    diagnostics,
+  }
+}
+
+#[test]
+fn test_evaluate_static() {
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "tokens.ts",
+      r#"
+        export * from "./palette";
+        export const spacing = { s: 4, m: 4 * 2 };
+        export default "tokens";
+      "#,
+    ),
+    (
+      "palette.ts",
+      r#"export const palette = { blue: "blue", grey: "grey" };"#,
+    ),
+  ]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import name, { palette, spacing } from "./tokens";
+    import * as tokens from "./tokens";
+
+    const width = "36px";
+    const base = { minWidth: width };
+    const sizes = [spacing.s, spacing.m];
+
+    export const styles = {
+      ...base,
+      color: palette["blue"],
+      gap: `${spacing.m}px`,
+      sizes: [...sizes, -1],
+      name,
+    };
+    export const grey = tokens.palette.grey + " " + tokens.default;
+    export enum Size { Small = 1 }
+  "#;
+
+  let result = evaluate_static(
+    filename,
+    source_text.to_string(),
+    vec!["styles".to_string(), "grey".to_string(), "Size".to_string()],
+    None,
+  );
+
+  assert_eq!(result.unknown, Vec::<String>::new());
+  assert_eq!(
+    result.values,
+    concat!(
+      r#"{"styles":{"minWidth":"36px","color":"blue","gap":"8px","sizes":[4,8,-1],"#,
+      r#""name":"tokens"},"grey":"grey tokens","Size":{"1":"Small","Small":1}}"#
+    )
+  );
+}
+
+#[test]
+fn test_evaluate_static_unknown() {
+  let source_text = r#"
+    const width = "36px";
+    export const random = Math.random();
+    export const styles = { width, height: window.innerHeight };
+    export const known = width;
+  "#;
+
+  let result = evaluate_static(
+    "/entry.ts".to_string(),
+    source_text.to_string(),
+    vec![
+      "random".to_string(),
+      "styles".to_string(),
+      "known".to_string(),
+    ],
+    None,
+  );
+
+  assert_eq!(result.values, r#"{"known":"36px"}"#);
+  assert_eq!(result.unknown, vec!["random", "styles"]);
+  assert_eq!(result.diagnostics.len(), 2);
+  assert!(result.diagnostics[0].contains("is not statically known"));
+}
+
+#[test]
+fn test_evaluate_static_mutations() {
+  let source_text = r#"
+    const styles = {};
+    styles.color = "red";
+    const sizes = [1, 2];
+    const total = register(sizes);
+    export { styles, sizes };
+    export const size = 4;
+  "#;
+
+  let result = evaluate_static(
+    "/entry.ts".to_string(),
+    source_text.to_string(),
+    vec![
+      "styles".to_string(),
+      "sizes".to_string(),
+      "size".to_string(),
+    ],
+    None,
+  );
+
+  assert_eq!(result.values, r#"{"size":4}"#);
+  assert_eq!(result.unknown, vec!["styles", "sizes"]);
+  assert!(result.diagnostics[0].contains("`styles` is modified by this statement"));
+  assert!(result.diagnostics[1].contains("`sizes` is modified by this statement"));
+}
+
+#[test]
+fn test_evaluate_static_star_exports() {
+  let fixture = crate::test_utils::Fixture::new(&[(
+    "palette.ts",
+    r#"
+      export const blue = "blue";
+      export const random = Math.random();
+    "#,
+  )]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    export * from "./missing";
+    export * from "./palette";
+  "#;
+
+  let result = evaluate_static(
+    filename,
+    source_text.to_string(),
+    vec!["blue".to_string(), "random".to_string(), "grey".to_string()],
+    None,
+  );
+
+  assert_eq!(result.values, r#"{"blue":"blue"}"#);
+  assert_eq!(result.unknown, vec!["random", "grey"]);
+  assert!(result.diagnostics[0].contains("The expression is not statically known"));
+  assert!(result.diagnostics[1].contains("`grey` is not exported"));
+  assert!(result.diagnostics[1].contains("./missing"));
+}
+
+#[test]
+fn test_evaluate_static_indirect_mutations() {
+  let source_text = r#"
+    const styles = { color: "blue" };
+    function init() {
+      styles.color = "red";
+    }
+    init();
+    const sizes = [1, 2];
+    function getSizes() {
+      return sizes;
+    }
+    export { styles, sizes, getSizes };
+  "#;
+
+  let result = evaluate_static(
+    "/entry.ts".to_string(),
+    source_text.to_string(),
+    vec!["styles".to_string(), "sizes".to_string()],
+    None,
+  );
+
+  assert_eq!(result.values, r#"{"sizes":[1,2]}"#);
+  assert_eq!(result.unknown, vec!["styles"]);
+  assert!(result.diagnostics[0].contains("`styles` is modified by this statement"));
+}
+
+#[test]
+fn test_evaluate_static_imported_mutations() {
+  let fixture = crate::test_utils::Fixture::new(&[(
+    "tokens.ts",
+    r#"
+      export const styles = { color: "blue" };
+      export const palette = { blue: "blue" };
+    "#,
+  )]);
+  let root = fixture.path();
+  let filename = root.join("entry.ts").to_string_lossy().to_string();
+  let source_text = r#"
+    import { styles } from "./tokens";
+    import * as tokens from "./tokens";
+    styles.color = "red";
+    tokens.palette.blue = "navy";
+    export const color = styles.color;
+    export const blue = tokens.palette.blue;
+  "#;
+
+  let result = evaluate_static(
+    filename,
+    source_text.to_string(),
+    vec!["color".to_string(), "blue".to_string()],
+    None,
+  );
+
+  assert_eq!(result.values, "{}");
+  assert_eq!(result.unknown, vec!["color", "blue"]);
+  assert!(result.diagnostics[0].contains("`styles` is modified by this statement"));
+  assert!(result.diagnostics[1].contains("`tokens` is modified by this statement"));
+}
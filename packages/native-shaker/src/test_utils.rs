@@ -0,0 +1,40 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_FIXTURE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Files of a test in a temporary directory, the directory is removed when the fixture is dropped.
+pub(crate) struct Fixture {
+  root: PathBuf,
+}
+
+impl Fixture {
+  /// Writes files to a new temporary directory. Each fixture gets its own directory, so tests can
+  /// run in parallel and don't see files of previous runs.
+  pub fn new(files: &[(&str, &str)]) -> Self {
+    let id = NEXT_FIXTURE_ID.fetch_add(1, Ordering::Relaxed);
+    let root = std::env::temp_dir().join(format!("native-shaker-{}-{id}", std::process::id()));
+
+    std::fs::create_dir_all(&root).unwrap();
+    for (path, content) in files {
+      let path = root.join(path);
+      std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+      std::fs::write(path, content).unwrap();
+    }
+
+    // Resolved paths have symlinks of the temporary directory resolved
+    Self {
+      root: root.canonicalize().unwrap(),
+    }
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.root
+  }
+}
+
+impl Drop for Fixture {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.root);
+  }
+}
@@ -1,21 +1,32 @@
+use crate::commonjs::CommonJsLowering;
 use crate::export_expand::ExportReplacer;
-use crate::{context::TransformContext, TransformOptions};
+use crate::module_resolve::{is_asset_request, resolve, Assets, ModuleResolver};
+use crate::object_members::{
+  exported_names, find_imported_members, find_prunable_objects, prune_object_members,
+  ExportRequests,
+};
+use crate::side_effects::SideEffects;
+use crate::statement_graph::StatementGraph;
+use crate::{
+  context::TransformContext, BareImportsPolicy, SideEffectsMode, SourceMap, TransformOptions,
+};
 use napi_derive::napi;
-use oxc_allocator::{Allocator, CloneIn, Vec as ArenaVec};
-use oxc_ast::ast::{Program, Statement};
-use oxc_ast::{AstBuilder, AstKind};
-use oxc_codegen::Codegen;
-use oxc_semantic::{
-  AstNode, AstNodeId, AstNodes, Reference, ReferenceId, ScopeId, ScopeTree, SemanticBuilder,
-  SymbolId, SymbolTable,
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+  ImportDeclarationSpecifier, Program, PropertyKind, Statement, TSTypeAnnotation,
+  VariableDeclarationKind,
 };
-use oxc_span::{GetSpan, Span};
+use oxc_ast::syntax_directed_operations::BoundNames;
+use oxc_ast::AstBuilder;
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_resolver::Resolver;
+use oxc_semantic::{Semantic, SemanticBuilder};
+use oxc_span::{GetSpan, SPAN};
 use oxc_transformer::Transformer;
 use oxc_traverse::TraverseCtx;
-use std::cell::{OnceCell, Ref, RefCell, RefMut};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
-use std::process::Output;
+use std::path::Path;
 // NOTE: Use JSDoc syntax for all doc comments, not rustdoc.
 // NOTE: Types must be aligned with [@types/babel__core](https://github.com/DefinitelyTyped/DefinitelyTyped/blob/master/types/babel__core/index.d.ts).
 
@@ -34,12 +45,12 @@ pub struct TransformResult {
   pub errors: Vec<String>,
 }
 
-fn pass_to_align_exports<'a>(
+pub(crate) fn pass_to_align_exports<'a>(
   allocator: &'a Allocator,
-  filename: &'a String,
-  source_text: &'a String,
+  filename: &'a str,
+  source_text: &'a str,
 ) -> TransformContext<'a> {
-  let ctx = TransformContext::new(allocator, &filename, &source_text);
+  let ctx = TransformContext::new(allocator, filename, source_text);
   let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
     .build(&ctx.program())
     .semantic;
@@ -49,476 +60,578 @@ fn pass_to_align_exports<'a>(
   let mut traverse_ctx = TraverseCtx::new(scope_tree, symbol_table, ctx.allocator);
   let ast_builder = AstBuilder::new(ctx.allocator);
 
-  ExportReplacer::new(ctx.allocator, ast_builder, filename)
-    .build(&mut ctx.program_mut(), &mut traverse_ctx);
+  let mut export_replacer = ExportReplacer::new(ctx.allocator, ast_builder, filename);
+  export_replacer.build(&mut ctx.program_mut(), &mut traverse_ctx);
+
+  ctx.add_diagnostics(export_replacer.errors);
 
   ctx
 }
 
-fn pass_to_treeshake<'a>(ctx: &'a TransformContext, only_exports_orig: Vec<&str>) {
-  let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
-    .build(&ctx.program())
-    .semantic;
-  // println!("{:#?}", ctx.source_text());
-  let nodes = semantic.nodes();
-  let symbols = semantic.symbols();
-
-  let mut only_exports = only_exports_orig.clone();
-  // let mut ids_of_nodes_to_keep: HashSet<AstNodeId> = HashSet::new();
-
-  // Pass for "default" export
-
-  if only_exports.contains(&"default") {
-    for stmt in ctx.program().body.iter() {
-      match stmt {
-        Statement::ExportNamedDeclaration(decl) => {
-          for specifier in &decl.specifiers {
-            if specifier.exported.name() == "default" {
-              let local_name = specifier.local.name().as_str();
-
-              only_exports.push(&local_name);
-            }
-          }
-        }
-        _ => {}
+/// Checks if a statement exports one of the requested names. `ExportReplacer` has already
+/// normalized exports to `export { local as exported }`, but statements that it could not process
+/// are still checked by their declared names.
+fn is_requested_export(stmt: &Statement, only_exports: &[&str]) -> bool {
+  match stmt {
+    Statement::ExportNamedDeclaration(decl) => {
+      let mut is_requested = decl
+        .specifiers
+        .iter()
+        .any(|specifier| only_exports.contains(&specifier.exported.name().as_str()));
+
+      if let Some(declaration) = &decl.declaration {
+        declaration.bound_names(&mut |ident| {
+          is_requested |= only_exports.contains(&ident.name.as_str());
+        });
       }
+
+      is_requested
     }
+    _ => false,
   }
+}
 
-  let root_scope_id = ScopeId::new(0);
-
-  // for symbol_id in symbols.iter() {
-  //   let symbol_scope_id = symbols.get_scope_id(symbol_id);
-  //
-  //   if symbol_scope_id == root_scope_id {
-  //     let symbol_flags = symbols.get_flags(symbol_id);
-  //
-  //     if symbol_flags.is_export() {
-  //       let symbol_name = symbols.get_name(symbol_id);
-  //
-  //       if only_exports.contains(&symbol_name) {
-  //         let ast_node = symbols.get_declaration(symbol_id);
-  //         let root_node_id = find_root_node_id(ast_node, nodes);
-  //
-  //         match root_node_id {
-  //           Some(root_node_id) => {
-  //             ids_of_nodes_to_keep.insert(root_node_id);
-  //           }
-  //           None => {}
-  //         }
-  //
-  //         let references = symbols.get_resolved_reference_ids(symbol_id);
-  //
-  //         for reference_id in references {
-  //           let reference = symbols.get_reference(*reference_id);
-  //           let node_id = reference.node_id();
-  //
-  //           let parent_kind = nodes.parent_kind(node_id);
-  //
-  //           match parent_kind {
-  //             Some(AstKind::ExportSpecifier(_)) => {
-  //               let root_node_id = find_root_node_id(node_id, nodes);
-  //
-  //               match root_node_id {
-  //                 Some(root_node_id) => {
-  //                   ids_of_nodes_to_keep.insert(root_node_id);
-  //                 }
-  //                 None => {}
-  //               }
-  //             }
-  //             _ => {}
-  //           }
-  //         }
-  //       }
-  //     }
-  //   }
-  // }
-
-  // for (id, symbolName) in symbols.names.iter().enumerate() {
-  //   if only_exports.contains(&symbolName.as_str()) {
-  //     // println!("name: {:?} {:#?}", id, symbolName);
-  //
-  //     let symbol_flags = symbols.flags[id];
-  //
-  //     if symbol_flags.is_export() {
-  //       // println!("exported: {:#?}", symbolName);
-  //
-  //       let declaration = symbols.declarations[id];
-  //
-  //       // let node = nodes.get_node(declaration);
-  //       // let root_node = find_root_node(node, nodes);
-  //
-  //       let root_node_id = find_root_node_id(declaration, nodes);
-  //
-  //       match root_node_id {
-  //         Some(root_node_id) => {
-  //           ids_of_nodes_to_keep.insert(root_node_id);
-  //         }
-  //         None => {}
-  //       }
-  //     }
-  //   }
-  // }
-
-  let mut baz_symbol_id = None;
-  let mut baz_symbol_span = None;
-  let mut baz_root_node_id = None;
-  let mut baz_root_node = None;
-
-  for symbol_id in symbols.iter() {
-    let symbol_scope_id = symbols.get_scope_id(symbol_id);
-
-    if symbol_scope_id == root_scope_id {
-      let symbol_flags = symbols.get_flags(symbol_id);
-
-      if symbol_flags.is_export() {
-        let symbol_name = symbols.get_name(symbol_id);
-
-        if only_exports.contains(&symbol_name) {
-          let ast_node = symbols.get_declaration(symbol_id);
-
-          baz_symbol_id = Some(symbol_id);
-          baz_symbol_span = Some(symbols.get_span(symbol_id));
-
-          baz_root_node_id = find_root_node_id(ast_node, nodes);
-          baz_root_node = Some(nodes.get_node(baz_root_node_id.unwrap()))
-        }
-      }
+/// Returns the source of an import without specifiers, i.e. `import "./polyfill"`.
+pub(crate) fn bare_import_source<'s>(stmt: &'s Statement) -> Option<&'s str> {
+  match stmt {
+    Statement::ImportDeclaration(decl)
+      if decl
+        .specifiers
+        .as_ref()
+        .map_or(true, |specifiers| specifiers.is_empty()) =>
+    {
+      Some(decl.source.value.as_str())
     }
+    _ => None,
   }
+}
 
-  // println!("baz_symbol_id: {:#?}", baz_symbol_id);
-  // println!("baz_symbol_span: {:#?}", baz_symbol_span);
-  // println!("baz_root_node_id: {:#?}", baz_root_node_id);
-  // println!("baz_root_node(span): {:#?}", baz_root_node.unwrap().span());
-
-  let mut ids_of_nodes_to_keep: HashSet<AstNodeId> = HashSet::new();
+/// Replaces imports of assets with string constants holding placeholders of their paths, i.e.
+/// `import logo from "./logo.svg"` becomes `const logo = "@wd40:/src/logo.svg:@wd40"`. Namespace
+/// imports become objects with the placeholder as `default`. Bare imports of assets are left to
+/// [`pass_to_prune_bare_imports`].
+fn pass_to_rewrite_asset_imports(
+  ctx: &TransformContext,
+  assets: &Assets,
+  resolve_path: impl Fn(&str) -> Option<String>,
+) {
+  let ast = AstBuilder::new(ctx.allocator);
+  let mut program = ctx.program_mut();
+  let body = std::mem::replace(&mut program.body, ast.vec());
+
+  for stmt in body {
+    let Statement::ImportDeclaration(decl) = &stmt else {
+      program.body.push(stmt);
+      continue;
+    };
+    let source = decl.source.value.as_str();
 
-  for symbol_id in symbols.iter() {
-    let symbol_scope_id = symbols.get_scope_id(symbol_id);
+    if bare_import_source(&stmt).is_some() || decl.import_kind.is_type() || !assets.is_asset(source)
+    {
+      program.body.push(stmt);
+      continue;
+    }
 
-    if symbol_scope_id == root_scope_id {
-      let symbol_flags = symbols.get_flags(symbol_id);
+    let path = resolve_path(source).unwrap_or_else(|| source.to_string());
+    let value = ast.str(&assets.placeholder(&path));
+
+    for specifier in decl.specifiers.iter().flatten() {
+      let local = specifier.local();
+      let init = match specifier {
+        ImportDeclarationSpecifier::ImportNamespaceSpecifier(_) => ast.expression_object(
+          SPAN,
+          ast.vec1(ast.object_property_kind_object_property(
+            SPAN,
+            PropertyKind::Init,
+            ast.property_key_identifier_name(SPAN, "default"),
+            ast.expression_string_literal(SPAN, value),
+            None,
+            false,
+            false,
+            false,
+          )),
+          None,
+        ),
+        _ => ast.expression_string_literal(SPAN, value),
+      };
+      let binding_pattern = ast.binding_pattern(
+        ast.binding_pattern_kind_from_binding_identifier(
+          ast.binding_identifier(local.span, local.name.clone()),
+        ),
+        Option::<TSTypeAnnotation>::None,
+        false,
+      );
+      let variable_declarator = ast.variable_declarator(
+        SPAN,
+        VariableDeclarationKind::Const,
+        binding_pattern,
+        Some(init),
+        false,
+      );
+      let var_declaration = ast.variable_declaration(
+        specifier.span(),
+        VariableDeclarationKind::Const,
+        ast.vec1(variable_declarator),
+        false,
+      );
+
+      program
+        .body
+        .push(ast.declaration_from_variable(var_declaration).into());
+    }
+  }
+}
 
-      if symbol_flags.is_export() {
-        let symbol_name = symbols.get_name(symbol_id);
+/// Lowers imports & exports of the shaken module to CommonJS, see [`CommonJsLowering`].
+fn pass_to_lower_commonjs(ctx: &TransformContext, resolve_path: &dyn Fn(&str) -> Option<String>) {
+  let ast_builder = AstBuilder::new(ctx.allocator);
+  let lowering = {
+    let program = ctx.program();
+    let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+      .build(&program)
+      .semantic;
 
-        if only_exports.contains(&symbol_name) {
-          let ast_node_id = symbols.get_declaration(symbol_id);
-          let ast_node = nodes.get_node(ast_node_id);
+    CommonJsLowering::new(ast_builder, resolve_path, &program, &semantic)
+  };
 
-          let root_node_id = find_root_node_id(ast_node_id, nodes);
-          let root_node = nodes.get_node(root_node_id.unwrap());
+  lowering.build(&mut ctx.program_mut());
+}
 
-          find_referenced_by_node_root_node_ids(
-            nodes,
-            symbols,
-            root_node,
-            &mut ids_of_nodes_to_keep,
-          );
+/// Removes bare imports that are not allowed by the policy, the remaining ones are kept by
+/// [`pass_to_treeshake`].
+fn pass_to_prune_bare_imports(
+  ctx: &TransformContext,
+  policy: BareImportsPolicy,
+  is_asset: impl Fn(&str) -> bool,
+  is_side_effect_free: impl Fn(&str) -> bool,
+) {
+  if policy == BareImportsPolicy::Keep {
+    return;
+  }
 
-          let references = symbols.get_resolved_reference_ids(symbol_id);
+  ctx.program_mut().body.retain(|stmt| {
+    let Some(source) = bare_import_source(stmt) else {
+      return true;
+    };
 
-          for reference_id in references {
-            let reference = symbols.get_reference(*reference_id);
-            let node_id = reference.node_id();
+    match policy {
+      BareImportsPolicy::Keep => true,
+      BareImportsPolicy::Drop => false,
+      BareImportsPolicy::KeepUnlessAsset => !is_asset(source),
+      BareImportsPolicy::DropIfPackageSideEffectFree => !is_side_effect_free(source),
+    }
+  });
+}
 
-            let parent_kind = nodes.parent_kind(node_id);
+/// Checks if a statement is kept regardless of the graph. Bare imports are always kept (see
+/// [`pass_to_prune_bare_imports`]), `export *` is kept unless the imported module is
+/// side-effect-free or when requested exports are not declared locally and can come from it.
+fn is_root_statement(
+  stmt: &Statement,
+  only_exports: &[&str],
+  has_unresolved_exports: bool,
+  is_side_effect_free: &impl Fn(&str) -> bool,
+) -> bool {
+  if bare_import_source(stmt).is_some() {
+    return true;
+  }
 
-            match parent_kind {
-              Some(AstKind::ExportSpecifier(_)) => {
-                let root_node_id = find_root_node_id(node_id, nodes);
+  match stmt {
+    Statement::ExportAllDeclaration(decl) => {
+      let is_used = match &decl.exported {
+        Some(exported) => only_exports.contains(&exported.name().as_str()),
+        None => has_unresolved_exports,
+      };
 
-                match root_node_id {
-                  Some(root_node_id) => {
-                    ids_of_nodes_to_keep.insert(root_node_id);
-                  }
-                  None => {}
-                }
-              }
-              _ => {}
-            }
-          }
-        }
-      }
+      is_used || !is_side_effect_free(decl.source.value.as_str())
     }
+    stmt => is_requested_export(stmt, only_exports),
   }
+}
+
+/// Finds statements with side effects that are not reachable, but reference reachable statements,
+/// i.e. `registerTokens(theme)` or `theme.color = "red"` when `theme` is kept. Removing them could
+/// change values of kept bindings.
+fn find_mutations_of_reachable(
+  program: &Program,
+  graph: &StatementGraph,
+  side_effects: &SideEffects,
+  reachable: &[bool],
+) -> Vec<usize> {
+  (0..graph.len())
+    .filter(|index| !reachable[*index])
+    .filter(|index| {
+      graph
+        .dependencies(*index)
+        .iter()
+        .any(|dependency| reachable[*dependency])
+    })
+    .filter(|index| side_effects.statement_may_have_side_effects(&program.body[*index]))
+    .collect()
+}
+
+/// Marks statements that are reachable from root statements. Unreachable statements with side
+/// effects that mutate reachable ones are reported or kept according to `side_effects_mode`.
+pub(crate) fn find_reachable_statements(
+  ctx: &TransformContext,
+  is_root: impl Fn(&Statement, &SideEffects) -> bool,
+  side_effects_mode: SideEffectsMode,
+  property_read_side_effects: bool,
+) -> Vec<bool> {
+  let program = ctx.program();
+  let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+    .build(&program)
+    .semantic;
+  let graph = StatementGraph::new(&semantic, &program);
+  let side_effects = SideEffects::new(ctx.source_text(), &ctx.trivias, &semantic, &program)
+    .with_property_read_side_effects(property_read_side_effects);
 
-  // println!("{:#?}", symbols.references);
-  // println!("{:#?}", symbols);
-
-  // let node_ids = symbols
-  //   .references
-  //   .iter()
-  //   .map(|reference| reference.node_id())
-  //   .collect::<Vec<_>>();
-  //
-  // println!(
-  //   "{:#?}",
-  //   node_ids
-  //     .iter()
-  //     .map(|id| nodes.get_node(*id))
-  //     .collect::<Vec<_>>()
-  // );
-
-  // for reference in symbols.references.iter().rev() {
-  //   match reference.symbol_id() {
-  //     None => {
-  //       // println!("no symbol id");
-  //       // println!("{:#?}", reference);
-  //     }
-  //     Some(symbol_id) => {
-  //       let declaration = symbols.get_declaration(symbol_id);
-  //
-  //       let referenced_node_id = declaration;
-  //       let reference_node_id = reference.node_id();
-
-  // let reference_root_node = find_root_node(reference_node, nodes);
-  // let referenced_root_node = find_root_node(referenced_node, nodes);
-  //
-  // // println!("referenced_node: {:#?}", referenced_node);
-  // // println!("reference_node: {:#?}", reference_node);
-  // // println!("reference_root_node: {:#?}", reference_root_node);
-  //
-  // match reference_root_node {
-  //   None => {}
-  //   Some(reference_root_node) => {
-  //     if ids_of_nodes_to_keep.contains(&reference_root_node.id()) {
-  //       // println!("keep: {:#?}", reference_root_node);
-  //
-  //       match referenced_root_node {
-  //         None => {}
-  //         Some(referenced_root_node) => {
-  //           ids_of_nodes_to_keep.push(referenced_root_node.id());
-  //         }
-  //       }
-  //     }
-  //   }
-  // }
-  //
-  // match referenced_root_node {
-  //   None => {}
-  //   Some(referenced_root_node) => {
-  //     if ids_of_nodes_to_keep.contains(&referenced_root_node.id()) {
-  //       // println!("keep: {:#?}", reference_root_node);
-  //
-  //       match reference_root_node {
-  //         None => {}
-  //         Some(reference_root_node) => {
-  //           ids_of_nodes_to_keep.push(reference_root_node.id());
-  //         }
-  //       }
-  //     }
-  //   }
-  // }
-
-  // let reference_root_node_id = find_root_node_id(reference_node_id, nodes);
-  //
-  // // println!("referenced_node: {:#?}", referenced_node);
-  // // println!("reference_node: {:#?}", reference_node);
-  // // println!("reference_root_node: {:#?}", reference_root_node);
-  //
-  // match reference_root_node_id {
-  //   None => {}
-  //   Some(reference_root_node_id) => {
-  //     if ids_of_nodes_to_keep.contains(&reference_root_node_id) {
-  //       // println!("keep: {:#?}", reference_root_node);
-  //
-  //       let referenced_root_node_id = find_root_node_id(referenced_node_id, nodes);
-  //
-  //       match referenced_root_node_id {
-  //         None => {}
-  //         Some(referenced_root_node_id) => {
-  //           ids_of_nodes_to_keep.insert(referenced_root_node_id);
-  //           continue;
-  //         }
-  //       }
-  //     }
-  //   }
-  // }
-
-  // match referenced_root_node_id {
-  //   None => {}
-  //   Some(referenced_root_node_id) => {
-  //     if ids_of_nodes_to_keep.contains(&referenced_root_node_id) {
-  //       // println!("keep: {:#?}", reference_root_node);
-  //
-  //       match reference_root_node_id {
-  //         None => {}
-  //         Some(reference_root_node_id) => {
-  //           ids_of_nodes_to_keep.insert(reference_root_node_id);
-  //         }
-  //       }
-  //     }
-  //   }
-  // }
-  // }
-  // }
-  // }
-
-  // println!("{:#?}", symbol_table);
-  // println!("{:#?}", scope_tree);
-
-  // println!("{:#?}", ctx.program());
-
-  // let mut traverse_ctx = TraverseCtx::new(semanticTable.1, semanticTable.0, ctx.allocator);
-  // let ast_builder = AstBuilder::new(ctx.allocator);
-
-  // let root_nodes_to_keep = ids_of_nodes_to_keep
-  //   .iter()
-  //   .map(|id| nodes.get_node(*id))
-  //   .collect::<Vec<&AstNode>>();
-  // let spans_to_keep = root_nodes_to_keep
-  //   .iter()
-  //   .map(|node| node.kind().span())
-  //   .collect::<Vec<_>>();
-
-  let spans_to_keep = ids_of_nodes_to_keep
+  let roots = program
+    .body
     .iter()
-    .map(|id| nodes.get_node(*id).kind().span())
-    .collect::<Vec<_>>();
-
-  // println!(
-  //   "{:#?}",
-  //   root_nodes_to_keep
-  //     .iter()
-  //     .map(|node| node.kind().span())
-  //     .collect::<Vec<_>>()
-  // );
-
-  let mut program = &mut ctx.program_mut();
-  let mut new_body = ArenaVec::new_in(ctx.allocator);
-
-  for node in program.body.iter() {
-    let should_keep = spans_to_keep
-      .iter()
-      .find(|span| span.start == node.span().start && span.end == node.span().end);
-
-    if should_keep.is_some() {
-      new_body.push(node.clone_in(ctx.allocator));
+    .enumerate()
+    .filter(|(_, stmt)| is_root(stmt, &side_effects))
+    .map(|(index, _)| index);
+
+  let mut reachable = graph.reachable_from(roots);
+
+  match side_effects_mode {
+    SideEffectsMode::Drop => {}
+    SideEffectsMode::Report => {
+      let diagnostics = find_mutations_of_reachable(&program, &graph, &side_effects, &reachable)
+        .into_iter()
+        .map(|index| {
+          OxcDiagnostic::warn("Removed a statement with side effects that uses kept bindings")
+            .with_help("Values of exports may differ from the original module")
+            .with_label(program.body[index].span())
+        })
+        .collect();
+
+      ctx.add_diagnostics(diagnostics);
     }
+    SideEffectsMode::Keep => loop {
+      // A kept mutation can pull new statements, which can be mutated by other statements
+      let mutations = find_mutations_of_reachable(&program, &graph, &side_effects, &reachable);
 
-    match node {
-      Statement::ExportAllDeclaration(_) => {
-        new_body.push(node.clone_in(ctx.allocator));
+      if mutations.is_empty() {
+        break;
       }
-      _ => {}
-    }
-  }
 
-  program.body = new_body;
-}
-
-// fn find_root_node<'a>(node: &'a AstNode, nodes: &'a AstNodes<'a>) -> Option<&'a AstNode<'a>> {
-//   let mut root_node: Option<&AstNode> = None;
-//
-//   for parent in nodes.iter_parents(node.id()) {
-//     if parent.id() == nodes.root_node().unwrap().id() {
-//       break;
-//     }
-//
-//     root_node = Some(parent);
-//   }
-//
-//   root_node
-// }
-
-fn find_root_node_id<'a>(node_id: AstNodeId, nodes: &'a AstNodes<'a>) -> Option<AstNodeId> {
-  let mut current_node: Option<AstNodeId> = None;
-  let root_node_id = nodes.root_node().unwrap().id();
-
-  for parent_id in nodes.ancestors(node_id) {
-    if parent_id == root_node_id {
-      break;
-    }
+      let roots = reachable
+        .iter()
+        .enumerate()
+        .filter(|(_, is_reachable)| **is_reachable)
+        .map(|(index, _)| index)
+        .chain(mutations);
 
-    current_node = Some(parent_id);
+      reachable = graph.reachable_from(roots);
+    },
   }
 
-  current_node
+  reachable
 }
 
-fn find_references_inside_span<'a>(
-  ast_nodes: &'a AstNodes,
-  symbol_table: &'a SymbolTable,
-  span: Span,
-) -> Vec<&'a Reference> {
-  let mut references = Vec::new();
+/// Removes statements of the program that are not marked to be kept.
+pub(crate) fn retain_statements(ctx: &TransformContext, should_keep: &[bool]) {
+  let mut program = ctx.program_mut();
+  let mut index = 0;
 
-  for reference in symbol_table.references.iter() {
-    let node = ast_nodes.get_node(reference.node_id());
-    let node_span = node.span();
+  program.body.retain(|_| {
+    let should_keep = should_keep[index];
+    index += 1;
 
-    if span.start <= node_span.start && span.end >= node_span.end {
-      references.push(reference);
-    }
+    should_keep
+  });
+}
+
+fn pass_to_treeshake(
+  ctx: &TransformContext,
+  only_exports: &[&str],
+  side_effects_mode: SideEffectsMode,
+  property_read_side_effects: bool,
+  is_side_effect_free: impl Fn(&str) -> bool,
+) {
+  let has_unresolved_exports = {
+    let program = ctx.program();
+
+    only_exports.iter().any(|name| {
+      !program
+        .body
+        .iter()
+        .any(|stmt| is_requested_export(stmt, &[*name]))
+    })
+  };
+
+  let reachable = find_reachable_statements(
+    ctx,
+    |stmt, _| {
+      is_root_statement(
+        stmt,
+        only_exports,
+        has_unresolved_exports,
+        &is_side_effect_free,
+      )
+    },
+    side_effects_mode,
+    property_read_side_effects,
+  );
+
+  retain_statements(ctx, &reachable);
+}
+
+/// Removes properties of exported object literals that are requested only by property paths, i.e.
+/// `tokens.colorBrand` keeps only `colorBrand` of `export const tokens = { ... }` unless the object
+/// escapes the module. Returns `true` when properties were removed.
+fn pass_to_prune_members(ctx: &TransformContext, members: &HashMap<&str, HashSet<&str>>) -> bool {
+  if members.is_empty() {
+    return false;
+  }
+
+  let objects = {
+    let program = ctx.program();
+    let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+      .build(&program)
+      .semantic;
+    let side_effects = SideEffects::new(ctx.source_text(), &ctx.trivias, &semantic, &program);
+
+    find_prunable_objects(&program, &semantic, &side_effects, members)
+  };
+
+  if objects.is_empty() {
+    return false;
   }
 
-  references
+  prune_object_members(&mut ctx.program_mut(), &objects);
+  true
 }
 
-fn get_root_node_of_reference<'a>(
-  ast_nodes: &'a AstNodes,
-  symbol_table: &'a SymbolTable,
-  reference: &'a Reference,
-) -> Option<&'a AstNode<'a>> {
-  let symbol_id = reference.symbol_id();
+/// Names that a shaken module uses from another module.
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleImport {
+  /// Import request as written in the code, i.e. `./tokens`.
+  pub source: String,
+  /// Imported names, `default` for default imports and `*` when the whole module is used. Empty
+  /// for bare imports. Names that are read only by their properties are property paths, i.e.
+  /// `tokens.colorBrand`.
+  pub names: Vec<String>,
+}
 
-  match symbol_id {
-    None => {}
-    Some(symbol_id) => {
-      let referenced_node_id = symbol_table.declarations[symbol_id];
-      let root_node_id = find_root_node_id(referenced_node_id, ast_nodes);
+/// Collects imports & `export *` of a shaken program, grouped by source. Names for `export *` are
+/// requested exports that are not declared by the module itself. Bindings that are read only by
+/// their properties import property paths, so the imported module can prune its objects too.
+fn collect_imports(
+  program: &Program,
+  semantic: &Semantic,
+  requests: &ExportRequests,
+) -> Vec<ModuleImport> {
+  let only_exports = requests.names.as_slice();
+  let with_members = |name: &str, members: Option<Vec<String>>| match members {
+    Some(members) => members
+      .into_iter()
+      .map(|member| format!("{name}.{member}"))
+      .collect(),
+    None => vec![name.to_string()],
+  };
+
+  let mut imports: Vec<ModuleImport> = Vec::new();
+  let mut add_names = |source: &str, names: Vec<String>| {
+    if let Some(import) = imports.iter_mut().find(|import| import.source == source) {
+      import.names.extend(names);
+    } else {
+      imports.push(ModuleImport {
+        source: source.to_string(),
+        names,
+      });
+    }
+  };
+
+  for stmt in &program.body {
+    match stmt {
+      Statement::ImportDeclaration(decl) => {
+        let names = decl
+          .specifiers
+          .iter()
+          .flatten()
+          .flat_map(|specifier| {
+            let name = match specifier {
+              ImportDeclarationSpecifier::ImportSpecifier(specifier) => specifier.imported.name(),
+              ImportDeclarationSpecifier::ImportDefaultSpecifier(_) => "default".into(),
+              ImportDeclarationSpecifier::ImportNamespaceSpecifier(_) => {
+                return vec!["*".to_string()];
+              }
+            };
+            let members =
+              specifier.local().symbol_id.get().and_then(|symbol_id| {
+                find_imported_members(program, semantic, symbol_id, requests)
+              });
 
-      match root_node_id {
-        None => {}
-        Some(root_node_id) => {
-          return Some(ast_nodes.get_node(root_node_id));
-        }
+            with_members(&name, members)
+          })
+          .collect();
+
+        add_names(decl.source.value.as_str(), names);
+      }
+      Statement::ExportAllDeclaration(decl) => {
+        let names = if decl.exported.is_some() || only_exports.contains(&"*") {
+          vec!["*".to_string()]
+        } else {
+          only_exports
+            .iter()
+            .filter(|name| {
+              !program
+                .body
+                .iter()
+                .any(|stmt| is_requested_export(stmt, &[**name]))
+            })
+            .flat_map(|name| {
+              let members = requests
+                .members
+                .get(name)
+                .map(|members| members.iter().map(|member| member.to_string()).collect());
+
+              with_members(name, members)
+            })
+            .collect()
+        };
+
+        add_names(decl.source.value.as_str(), names);
       }
+      _ => {}
     }
   }
 
-  None
+  for import in &mut imports {
+    import.names.sort();
+    import.names.dedup();
+  }
+
+  imports
 }
 
-fn find_referenced_by_node_root_node_ids<'a>(
-  ast_nodes: &'a AstNodes,
-  symbol_table: &'a SymbolTable,
-  node: &'a AstNode,
-  mut set: &mut HashSet<AstNodeId>,
-) {
-  set.insert(node.id());
+#[napi(object)]
+pub struct TransformInner {
+  pub output: String,
+  /// Maps the shaken code to the original file, only present when requested with
+  /// {@link TransformOptions#sourcemap}.
+  pub map: Option<SourceMap>,
+  /// Parse errors and statements that could not be processed. Unsupported statements are kept in
+  /// the output as is, so the module should be evaluated without shaking when this is not empty.
+  /// Also contains warnings about removed side effects when {@link TransformOptions#sideEffects}
+  /// is `report`.
+  pub errors: Vec<String>,
+  /// Modules that are still imported after shaking & names used from them.
+  pub imports: Vec<ModuleImport>,
+}
 
-  // println!("find_referenced_by_node_root_node_ids: node {:#?}", node);
+/// Strips TypeScript & JSX from the (already shaken) program, so the output can be evaluated as
+/// plain JavaScript.
+fn pass_to_transpile(ctx: &TransformContext, transform_options: oxc_transformer::TransformOptions) {
+  let (symbols, scopes) = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+    .build(&ctx.program())
+    .semantic
+    .into_symbol_table_and_scope_tree();
+
+  let result = Transformer::new(
+    ctx.allocator,
+    ctx.file_path(),
+    ctx.source_type(),
+    ctx.source_text(),
+    ctx.trivias.clone(),
+    transform_options,
+  )
+  .build_with_symbols_and_scopes(symbols, scopes, &mut ctx.program_mut());
+
+  ctx.add_diagnostics(result.errors);
+}
 
-  let references = find_references_inside_span(ast_nodes, symbol_table, node.span());
+pub(crate) fn pass_to_output(
+  ctx: &TransformContext,
+  transform_options: Option<oxc_transformer::TransformOptions>,
+  source_map: bool,
+  output_json: bool,
+) -> TransformInner {
+  if let Some(transform_options) = transform_options {
+    pass_to_transpile(ctx, transform_options);
+  }
 
-  for reference in references {
-    let referenced_root_node = get_root_node_of_reference(ast_nodes, symbol_table, reference);
+  let errors = ctx.take_and_render_reports();
 
-    match referenced_root_node {
-      None => {}
-      Some(referenced_root_node) => {
-        // println!("find_referenced_by_node_root_node_ids: L reference {:#?}", reference);
+  let mut output = String::new();
+  let mut map = None;
 
-        // println!("symbol_table: {:#?}", symbol_table);
-        // println!("find_referenced_by_node_root_node_ids: L root {:#?}", referenced_root_node);
+  // A source map describes the generated code, so the code is emitted instead of JSON
+  if source_map || !output_json {
+    let codegen = if source_map {
+      ctx.codegen_with_source_map()
+    } else {
+      ctx.codegen()
+    };
+    let code = codegen.build(&ctx.program());
 
-        if set.contains(&referenced_root_node.id()) {
-          continue;
-        }
+    output = code.source_text;
+    map = code.source_map.map(SourceMap::from);
+  } else {
+    output = serde_json::to_string(ctx.program().deref()).unwrap();
+  }
 
-        find_referenced_by_node_root_node_ids(ast_nodes, symbol_table, referenced_root_node, set);
-      }
-    }
+  TransformInner {
+    output,
+    map,
+    errors,
+    imports: Vec::new(),
   }
 }
 
-#[napi(object)]
-pub struct TransformInner {
-  pub output: String,
-  pub errors: Vec<String>,
+/// Options of [`transform_inner`], napi's {@link TransformOptions} are converted to them.
+#[derive(Default)]
+pub struct TransformInnerOptions<'a> {
+  /// Strips TypeScript & JSX from the output when set.
+  pub transpile: Option<oxc_transformer::TransformOptions>,
+  /// What happens to statements with side effects that are not required by requested exports.
+  pub side_effects: SideEffectsMode,
+  /// Reads of properties of imports & globals are considered to have side effects.
+  pub property_read_side_effects: bool,
+  /// Generate a source map for the output.
+  pub source_map: bool,
+  /// Return the ESTree-compatible AST serialized to JSON instead of the code. Ignored when
+  /// `source_map` is set, the map only describes the code.
+  pub output_json: bool,
+  /// Which bare imports, i.e. `import "./polyfill"`, are kept in the output.
+  pub bare_imports: BareImportsPolicy,
+  /// Resolves bare imports & `export *` to check package.json "sideEffects" of imported modules.
+  /// Without it, all of them are considered to have side effects. Paths of assets are resolved
+  /// with it too, otherwise their import requests are used as is.
+  pub resolver: Option<&'a Resolver>,
+  /// Replaces imports of assets with string placeholders when set.
+  pub assets: Option<Assets>,
+  /// Lowers imports & exports to `require()` & `exports` after tree shaking.
+  pub commonjs: bool,
+}
+
+impl From<TransformOptions> for TransformInnerOptions<'_> {
+  fn from(options: TransformOptions) -> Self {
+    let side_effects = options.side_effects.unwrap_or_default();
+    let property_read_side_effects = options.property_read_side_effects.unwrap_or_default();
+    let bare_imports = options.bare_imports.unwrap_or_default();
+    let assets = options.assets.clone().map(Into::into);
+    let source_map = options.sourcemap.unwrap_or_default();
+    let commonjs = options.commonjs.unwrap_or_default();
+    let should_transpile =
+      options.typescript.is_some() || options.react.is_some() || options.es2015.is_some();
+
+    Self {
+      transpile: should_transpile.then(|| options.into()),
+      side_effects,
+      property_read_side_effects,
+      source_map,
+      output_json: false,
+      bare_imports,
+      resolver: None,
+      assets,
+      commonjs,
+    }
+  }
 }
 
 pub fn transform_inner<'a>(
@@ -526,48 +639,87 @@ pub fn transform_inner<'a>(
   filename: &'a String,
   source_text: &'a String,
   only_exports: Vec<&str>,
-  output_json: bool,
+  options: TransformInnerOptions,
 ) -> TransformInner {
-  let ctx_align_exports = pass_to_align_exports(allocator, filename, source_text);
+  let ctx = pass_to_align_exports(allocator, filename, source_text);
+
+  let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+  let is_side_effect_free = |source: &str| {
+    options
+      .resolver
+      .is_some_and(|resolver| resolve(resolver, directory, source).is_side_effect_free())
+  };
+  let is_asset = |source: &str| {
+    options.assets.as_ref().map_or_else(
+      || is_asset_request(source),
+      |assets| assets.is_asset(source),
+    )
+  };
+
+  let resolve_path = |source: &str| {
+    options
+      .resolver
+      .and_then(|resolver| resolve(resolver, directory, source).path)
+  };
+
+  if let Some(assets) = &options.assets {
+    pass_to_rewrite_asset_imports(&ctx, assets, resolve_path);
+  }
 
-  if only_exports.contains(&"*") {
-    let errors = ctx_align_exports.take_and_render_reports();
-    if output_json {
-      let output = serde_json::to_string(ctx_align_exports.program().deref()).unwrap();
+  pass_to_prune_bare_imports(&ctx, options.bare_imports, &is_asset, &is_side_effect_free);
 
-      return TransformInner { output, errors };
-    }
+  let export_names = exported_names(&ctx.program());
+  let requests = ExportRequests::new(&only_exports, |name| export_names.contains(name));
 
-    let code_align_exports = ctx_align_exports
-      .codegen()
-      .build(&ctx_align_exports.program());
+  // Tree shaking works on the same AST, all passes have fresh semantic data built from it, so
+  // there is no need to print & parse the code between them
+  if !requests.names.contains(&"*") {
+    pass_to_treeshake(
+      &ctx,
+      &requests.names,
+      options.side_effects,
+      options.property_read_side_effects,
+      &is_side_effect_free,
+    );
 
-    return TransformInner {
-      output: code_align_exports.source_text,
-      errors,
-    };
+    // removed properties can be the last users of other bindings
+    if pass_to_prune_members(&ctx, &requests.members) {
+      pass_to_treeshake(
+        &ctx,
+        &requests.names,
+        options.side_effects,
+        options.property_read_side_effects,
+        &is_side_effect_free,
+      );
+    }
   }
 
-  let code_align_exports = ctx_align_exports
-    .codegen()
-    .build(&ctx_align_exports.program());
-  let ctx_treeshake = TransformContext::new(&allocator, &filename, &code_align_exports.source_text);
+  let mut transpile = options.transpile;
 
-  pass_to_treeshake(&ctx_treeshake, only_exports);
+  // JSX becomes calls that reference imports, i.e. of "react/jsx-runtime", they are collected &
+  // lowered with other imports
+  if options.commonjs {
+    if let Some(transform_options) = transpile.take() {
+      pass_to_transpile(&ctx, transform_options);
+    }
+  }
 
-  let errors = ctx_treeshake.take_and_render_reports();
+  let imports = {
+    let program = ctx.program();
+    let semantic = SemanticBuilder::new(ctx.source_text(), ctx.source_type())
+      .build(&program)
+      .semantic;
 
-  if output_json {
-    let output = serde_json::to_string(ctx_treeshake.program().deref()).unwrap();
+    collect_imports(&program, &semantic, &requests)
+  };
 
-    return TransformInner { output, errors };
+  if options.commonjs {
+    pass_to_lower_commonjs(&ctx, &resolve_path);
   }
 
-  let code_treeshake = ctx_treeshake.codegen().build(&ctx_treeshake.program());
-
   TransformInner {
-    output: code_treeshake.source_text,
-    errors,
+    imports,
+    ..pass_to_output(&ctx, transpile, options.source_map, options.output_json)
   }
 }
 
@@ -576,11 +728,18 @@ pub fn transform_inner<'a>(
 /// @param filename The name of the file being transformed. If this is a
 /// relative path, consider setting the {@link TransformOptions#cwd} option..
 /// @param sourceText the source code itself
+/// @param onlyExports Names of exports that should be kept, `*` disables tree shaking. Property
+/// paths, i.e. `tokens.colorBrand`, keep only requested properties of exported object literals.
 /// @param options The options for the transformation. See {@link
-/// TransformOptions} for more information.
+/// TransformOptions} for more information. TypeScript & JSX are kept in the
+/// output as is unless `typescript`, `react` or `es2015` options are passed.
+/// @param resolver A resolver from {@link createResolver}, it resolves paths of
+/// imports for `commonjs` & `assets` options and "sideEffects" of packages.
 ///
 /// @returns an object containing the transformed code, source maps, and any
-/// errors that occurred during parsing or transformation.
+/// errors that occurred during parsing or transformation. The code is an
+/// ESTree-compatible AST serialized to JSON unless `commonjs` or `sourcemap` is
+/// set.
 #[allow(clippy::needless_pass_by_value)]
 #[napi]
 pub fn transform(
@@ -588,17 +747,33 @@ pub fn transform(
   source_text: String,
   only_exports: Vec<&str>,
   options: Option<TransformOptions>,
+  resolver: Option<&ModuleResolver>,
 ) -> TransformInner {
   let allocator = Allocator::default();
-  let result = transform_inner(&allocator, &filename, &source_text, only_exports, true);
+  let resolver = resolver.map(ModuleResolver::resolver);
+  let options: TransformInnerOptions = options.map(Into::into).unwrap_or_default();
+  let result = transform_inner(
+    &allocator,
+    &filename,
+    &source_text,
+    only_exports,
+    TransformInnerOptions {
+      output_json: !options.commonjs,
+      resolver: resolver.as_deref(),
+      ..options
+    },
+  );
 
   result
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::transformer::transform_inner;
+  use crate::module_resolve::Assets;
+  use crate::transformer::{transform_inner, ModuleImport, TransformInnerOptions};
+  use crate::{BareImportsPolicy, SideEffectsMode, TransformOptions};
   use oxc_allocator::Allocator;
+  use oxc_resolver::Resolver;
   use pretty_assertions::assert_eq;
 
   pub fn assert_fixture(input: &str, expected: &str, only_exports: Vec<&str>) {
@@ -608,12 +783,178 @@ mod tests {
       &"source.js".to_string(),
       &input.to_string(),
       only_exports,
-      false,
+      TransformInnerOptions::default(),
     );
     let result_code = result.output.replace("\t", "  ");
 
     assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
   }
+
+  pub fn assert_transpiled_fixture(
+    filename: &str,
+    input: &str,
+    expected: &str,
+    only_exports: Vec<&str>,
+  ) {
+    let allocator = Allocator::default();
+    let result = transform_inner(
+      &allocator,
+      &filename.to_string(),
+      &input.to_string(),
+      only_exports,
+      TransformInnerOptions {
+        transpile: Some(TransformOptions::default().into()),
+        ..TransformInnerOptions::default()
+      },
+    );
+    let result_code = result.output.replace("\t", "  ");
+
+    assert_eq!(result.errors.len(), 0);
+    assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
+  }
+
+  pub fn assert_side_effects_fixture(
+    input: &str,
+    expected: &str,
+    only_exports: Vec<&str>,
+    side_effects: SideEffectsMode,
+  ) -> Vec<String> {
+    let allocator = Allocator::default();
+    let result = transform_inner(
+      &allocator,
+      &"source.js".to_string(),
+      &input.to_string(),
+      only_exports,
+      TransformInnerOptions {
+        side_effects,
+        ..TransformInnerOptions::default()
+      },
+    );
+    let result_code = result.output.replace("\t", "  ");
+
+    assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
+
+    result.errors
+  }
+
+  pub fn assert_resolved_fixture(
+    filename: &str,
+    input: &str,
+    expected: &str,
+    only_exports: Vec<&str>,
+    resolver: &Resolver,
+  ) {
+    let allocator = Allocator::default();
+    let result = transform_inner(
+      &allocator,
+      &filename.to_string(),
+      &input.to_string(),
+      only_exports,
+      TransformInnerOptions {
+        resolver: Some(resolver),
+        ..TransformInnerOptions::default()
+      },
+    );
+    let result_code = result.output.replace("\t", "  ");
+
+    assert_eq!(result.errors.len(), 0);
+    assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
+  }
+
+  pub fn assert_bare_imports_fixture(input: &str, expected: &str, bare_imports: BareImportsPolicy) {
+    let allocator = Allocator::default();
+    let result = transform_inner(
+      &allocator,
+      &"source.js".to_string(),
+      &input.to_string(),
+      vec!["foo"],
+      TransformInnerOptions {
+        bare_imports,
+        ..TransformInnerOptions::default()
+      },
+    );
+    let result_code = result.output.replace("\t", "  ");
+
+    assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
+  }
+
+  pub fn assert_assets_fixture(
+    filename: &str,
+    input: &str,
+    expected: &str,
+    assets: Assets,
+    resolver: Option<&Resolver>,
+  ) {
+    let allocator = Allocator::default();
+    let result = transform_inner(
+      &allocator,
+      &filename.to_string(),
+      &input.to_string(),
+      vec!["classes"],
+      TransformInnerOptions {
+        assets: Some(assets),
+        resolver,
+        ..TransformInnerOptions::default()
+      },
+    );
+    let result_code = result.output.replace("\t", "  ");
+
+    assert_eq!(result.errors.len(), 0);
+    assert_eq!(result_code.trim(), textwrap::dedent(expected).trim());
+  }
+
+  pub fn transform_imports(input: &str, only_exports: Vec<&str>) -> Vec<ModuleImport> {
+    let allocator = Allocator::default();
+    let result = transform_inner(
+      &allocator,
+      &"source.js".to_string(),
+      &input.to_string(),
+      only_exports,
+      TransformInnerOptions::default(),
+    );
+
+    result.imports
+  }
+
+  pub fn transform_source_map(input: &str, only_exports: Vec<&str>) -> oxc_sourcemap::SourceMap {
+    let allocator = Allocator::default();
+    let result = transform_inner(
+      &allocator,
+      &"source.js".to_string(),
+      &input.to_string(),
+      only_exports,
+      TransformInnerOptions {
+        source_map: true,
+        ..TransformInnerOptions::default()
+      },
+    );
+    let map = result.map.expect("source map should be generated");
+
+    oxc_sourcemap::SourceMap::from_json_string(
+      &serde_json::json!({
+        "version": 3,
+        "sources": map.sources,
+        "sourcesContent": map.sources_content,
+        "names": map.names,
+        "mappings": map.mappings,
+      })
+      .to_string(),
+    )
+    .unwrap()
+  }
+
+  pub fn transform_errors(input: &str, only_exports: Vec<&str>) -> Vec<String> {
+    let allocator = Allocator::default();
+    let result = transform_inner(
+      &allocator,
+      &"source.js".to_string(),
+      &input.to_string(),
+      only_exports,
+      TransformInnerOptions::default(),
+    );
+
+    result.errors
+  }
 }
 
 #[test]
@@ -656,6 +997,95 @@ fn test_obj() {
 
 #[test]
 
+fn test_obj_members() {
+  let input = r#"
+    const foo = "foo";
+    const baz = "baz";
+    export const foobaz = { foo: baz, bar: foo, qux: "qux" };
+    export const bar = foobaz.bar;
+  "#;
+  let output = r#"
+    const foo = "foo";
+    const baz = "baz";
+    const foobaz = { foo: baz, bar: foo };
+    export { foobaz };
+    const bar = foobaz.bar;
+    export { bar };
+  "#;
+
+  tests::assert_fixture(input, output, vec!["foobaz.foo", "bar"]);
+
+  let output = r#"
+    const baz = "baz";
+    const foobaz = { foo: baz };
+    export { foobaz };
+  "#;
+
+  tests::assert_fixture(input, output, vec!["foobaz.foo.length"]);
+}
+
+#[test]
+
+fn test_obj_members_escape() {
+  let input = r#"
+    const foo = "foo";
+    export const foobaz = { foo, bar: "bar" };
+    export const copy = { ...foobaz };
+  "#;
+  let output = r#"
+    const foo = "foo";
+    const foobaz = { foo, bar: "bar" };
+    export { foobaz };
+    const copy = { ...foobaz };
+    export { copy };
+  "#;
+
+  tests::assert_fixture(input, output, vec!["foobaz.foo", "copy"]);
+  tests::assert_fixture(
+    input,
+    r#"
+    const foo = "foo";
+    const foobaz = { foo, bar: "bar" };
+    export { foobaz };
+    "#,
+    vec!["foobaz.foo", "foobaz"],
+  );
+}
+
+#[test]
+
+fn test_obj_members_spread() {
+  let input = r#"
+    const base = { color: "red" };
+    export const tokens = { ...base, spacing: 4, color: "blue" };
+  "#;
+  let output = r#"
+    const base = { color: "red" };
+    const tokens = { ...base, spacing: 4, color: "blue" };
+    export { tokens };
+  "#;
+
+  tests::assert_fixture(input, output, vec!["tokens.spacing"]);
+}
+
+#[test]
+
+fn test_obj_members_string_export_name() {
+  let input = r#"
+    const x = { b: 1, c: 2 };
+    export { x as "a.b" };
+    export const a = { b: 3 };
+  "#;
+  let output = r#"
+    const x = { b: 1, c: 2 };
+    export { x as "a.b" };
+  "#;
+
+  tests::assert_fixture(input, output, vec!["a.b"]);
+}
+
+#[test]
+
 fn test_exports() {
   let input = r#"
     const foo = "foo";
@@ -770,6 +1200,254 @@ fn test_export_all() {
 
 #[test]
 
+fn test_bare_imports() {
+  let input = r#"
+    import "./polyfills";
+    export * from "./mod";
+    export const foo = "foo";
+  "#;
+  let output = r#"
+    import "./polyfills";
+    export * from "./mod";
+    const foo = "foo";
+    export { foo };
+  "#;
+
+  tests::assert_fixture(input, output, vec!["foo"]);
+}
+
+#[test]
+
+fn test_bare_imports_policies() {
+  let input = r#"
+    import "./polyfill";
+    import "./global.css";
+    export const foo = "foo";
+  "#;
+
+  tests::assert_bare_imports_fixture(
+    input,
+    r#"
+      import "./polyfill";
+      import "./global.css";
+      const foo = "foo";
+      export { foo };
+    "#,
+    BareImportsPolicy::Keep,
+  );
+  tests::assert_bare_imports_fixture(
+    input,
+    r#"
+      const foo = "foo";
+      export { foo };
+    "#,
+    BareImportsPolicy::Drop,
+  );
+  tests::assert_bare_imports_fixture(
+    input,
+    r#"
+      import "./polyfill";
+      const foo = "foo";
+      export { foo };
+    "#,
+    BareImportsPolicy::KeepUnlessAsset,
+  );
+}
+
+#[test]
+
+fn test_package_side_effects() {
+  use oxc_resolver::{ResolveOptions, Resolver};
+
+  let fixture = crate::test_utils::Fixture::new(&[
+    (
+      "node_modules/pure/package.json",
+      r#"{ "main": "index.js", "sideEffects": false }"#,
+    ),
+    ("node_modules/pure/index.js", "export const foo = 1;"),
+    (
+      "node_modules/styles/package.json",
+      r#"{ "main": "index.js", "sideEffects": ["*.css"] }"#,
+    ),
+    ("node_modules/styles/index.js", "export const foo = 1;"),
+    ("node_modules/styles/button.css", ""),
+    (
+      "node_modules/effectful/package.json",
+      r#"{ "main": "index.js" }"#,
+    ),
+    ("node_modules/effectful/index.js", "export const foo = 1;"),
+  ]);
+  let root = fixture.path();
+
+  let input = r#"
+    import "pure";
+    import "styles";
+    import "styles/button.css";
+    import "effectful";
+    export * from "pure";
+    export * from "effectful";
+    export const foo = "foo";
+  "#;
+  let output = r#"
+    import "styles/button.css";
+    import "effectful";
+    export * from "effectful";
+    const foo = "foo";
+    export { foo };
+  "#;
+
+  let resolver = Resolver::new(ResolveOptions::default());
+  let filename = root.join("source.js").to_string_lossy().to_string();
+
+  tests::assert_resolved_fixture(&filename, input, output, vec!["foo"], &resolver);
+  // `export *` from a side-effect-free module is kept when it can provide requested exports
+  tests::assert_resolved_fixture(
+    &filename,
+    r#"export * from "pure";"#,
+    r#"export * from "pure";"#,
+    vec!["bar"],
+    &resolver,
+  );
+}
+
+#[test]
+
+fn test_asset_imports() {
+  use crate::module_resolve::Assets;
+
+  let input = r#"
+    import logo from "./logo.svg";
+    import { icon, unused } from "./icons.png";
+    import * as sprites from "./sprites.svg";
+    import "./global.css";
+    import { color } from "./tokens";
+    export const classes = logo + icon + sprites.default + color;
+  "#;
+  let output = r#"
+    const logo = "@wd40:./logo.svg:@wd40";
+    const icon = "@wd40:./icons.png:@wd40";
+    const sprites = { default: "@wd40:./sprites.svg:@wd40" };
+    import "./global.css";
+    import { color } from "./tokens";
+    const classes = logo + icon + sprites.default + color;
+    export { classes };
+  "#;
+
+  tests::assert_assets_fixture("source.js", input, output, Assets::default(), None);
+}
+
+#[test]
+
+fn test_asset_imports_options() {
+  use crate::module_resolve::Assets;
+  use crate::AssetOptions;
+  use oxc_resolver::{ResolveOptions, Resolver};
+
+  let fixture = crate::test_utils::Fixture::new(&[("logo.svg", "<svg />"), ("button.style", "")]);
+  let root = fixture.path();
+
+  let input = r#"
+    import logo from "./logo.svg";
+    import button from "./button.style";
+    export const classes = logo + button;
+  "#;
+  let output = format!(
+    r#"
+      import {{ default as logo }} from "./logo.svg";
+      const button = "asset(${}:asset)";
+      const classes = logo + button;
+      export {{ classes }};
+    "#,
+    root.join("button.style").to_string_lossy()
+  );
+
+  let assets = Assets::from(AssetOptions {
+    extensions: Some(vec![".style".to_string()]),
+    prefix: Some("asset($".to_string()),
+    suffix: Some(":asset)".to_string()),
+  });
+  let resolver = Resolver::new(ResolveOptions::default());
+  let filename = root.join("source.js").to_string_lossy().to_string();
+
+  tests::assert_assets_fixture(&filename, input, &output, assets, Some(&resolver));
+}
+
+#[test]
+
+fn test_imports() {
+  let input = r#"
+    import "./polyfill";
+    import tokens, { shorthands, unused } from "./tokens";
+    import * as utils from "./utils";
+    import { gap } from "./tokens";
+    export * from "./colors";
+    export const classes = [tokens, shorthands, gap, utils];
+    export const foo = "foo";
+  "#;
+
+  assert_eq!(
+    tests::transform_imports(input, vec!["classes", "brand"]),
+    vec![
+      ModuleImport {
+        source: "./polyfill".to_string(),
+        names: vec![],
+      },
+      ModuleImport {
+        source: "./tokens".to_string(),
+        names: vec![
+          "default".to_string(),
+          "gap".to_string(),
+          "shorthands".to_string()
+        ],
+      },
+      ModuleImport {
+        source: "./utils".to_string(),
+        names: vec!["*".to_string()],
+      },
+      ModuleImport {
+        source: "./colors".to_string(),
+        names: vec!["brand".to_string()],
+      },
+    ]
+  );
+}
+
+#[test]
+
+fn test_imports_members() {
+  let input = r#"
+    import { tokens, palette } from "./tokens";
+    import { shorthands } from "./shorthands";
+    export * from "./colors";
+    export { palette };
+    export const classes = [tokens.colorBrand, tokens.colorNeutral.hover, shorthands];
+  "#;
+
+  assert_eq!(
+    tests::transform_imports(input, vec!["classes", "palette.blue", "brand.primary"]),
+    vec![
+      ModuleImport {
+        source: "./tokens".to_string(),
+        names: vec![
+          "palette.blue".to_string(),
+          "tokens.colorBrand".to_string(),
+          "tokens.colorNeutral".to_string()
+        ],
+      },
+      ModuleImport {
+        source: "./shorthands".to_string(),
+        names: vec!["shorthands".to_string()],
+      },
+      ModuleImport {
+        source: "./colors".to_string(),
+        names: vec!["brand.primary".to_string()],
+      },
+    ]
+  );
+}
+
+#[test]
+
 fn test_skip_shake() {
   let input = r#"
     const foo = "foo";
@@ -839,3 +1517,231 @@ fn test_order() {
 
   tests::assert_fixture(input, output, vec!["baz"]);
 }
+
+#[test]
+
+fn test_side_effects_keep() {
+  let input = r#"
+    import { registerTokens } from "./registry";
+    import { create } from "./create";
+    const theme = { color: "red" };
+    const extra = { size: 1 };
+    registerTokens(theme);
+    Object.assign(theme, extra);
+    theme.bar = 1;
+    console.log("x");
+    const unused = create();
+    export { theme };
+  "#;
+  let output = r#"
+    import { registerTokens } from "./registry";
+    const theme = { color: "red" };
+    const extra = { size: 1 };
+    registerTokens(theme);
+    Object.assign(theme, extra);
+    theme.bar = 1;
+    export { theme };
+  "#;
+
+  let errors =
+    tests::assert_side_effects_fixture(input, output, vec!["theme"], SideEffectsMode::Keep);
+  assert_eq!(errors.len(), 0);
+}
+
+#[test]
+
+fn test_side_effects_keep_transitive() {
+  let input = r#"
+    const base = { color: "red" };
+    const theme = { base };
+    const overrides = { color: "blue" };
+    theme.base = overrides;
+    Object.assign(overrides, { size: 1 });
+    export { theme };
+  "#;
+  let output = r#"
+    const base = { color: "red" };
+    const theme = { base };
+    const overrides = { color: "blue" };
+    theme.base = overrides;
+    Object.assign(overrides, { size: 1 });
+    export { theme };
+  "#;
+
+  let errors =
+    tests::assert_side_effects_fixture(input, output, vec!["theme"], SideEffectsMode::Keep);
+  assert_eq!(errors.len(), 0);
+}
+
+#[test]
+
+fn test_side_effects_keep_pure_annotations() {
+  let input = r#"
+    import { createTheme, registerTokens } from "./theme";
+    /*#__NO_SIDE_EFFECTS__*/ function extend(theme) {
+      return theme;
+    }
+    export const theme = { color: "red" };
+    const dark = /*#__PURE__*/ createTheme(theme);
+    const light = extend(theme);
+    registerTokens(theme);
+  "#;
+  let output = r#"
+    import { registerTokens } from "./theme";
+    const theme = { color: "red" };
+    export { theme };
+    registerTokens(theme);
+  "#;
+
+  let errors =
+    tests::assert_side_effects_fixture(input, output, vec!["theme"], SideEffectsMode::Keep);
+  assert_eq!(errors.len(), 0);
+}
+
+#[test]
+
+fn test_side_effects_keep_property_reads() {
+  let input = r#"
+    import { theme } from "./theme";
+    export const color = theme.color;
+    theme.activate;
+    const unused = "unused";
+  "#;
+  let output = r#"
+    import { theme } from "./theme";
+    const color = theme.color;
+    export { color };
+    theme.activate;
+  "#;
+
+  let allocator = Allocator::default();
+  let result = transform_inner(
+    &allocator,
+    &"source.js".to_string(),
+    &input.to_string(),
+    vec!["color"],
+    TransformInnerOptions {
+      side_effects: SideEffectsMode::Keep,
+      property_read_side_effects: true,
+      ..TransformInnerOptions::default()
+    },
+  );
+  let result_code = result.output.replace("\t", "  ");
+
+  assert_eq!(result.errors.len(), 0);
+  assert_eq!(result_code.trim(), textwrap::dedent(output).trim());
+}
+
+#[test]
+
+fn test_side_effects_report() {
+  let input = r#"
+    import { registerTokens } from "./registry";
+    const theme = { color: "red" };
+    registerTokens(theme);
+    theme.bar = 1;
+    console.log("x");
+    export { theme };
+  "#;
+  let output = r#"
+    const theme = { color: "red" };
+    export { theme };
+  "#;
+
+  let errors =
+    tests::assert_side_effects_fixture(input, output, vec!["theme"], SideEffectsMode::Report);
+  assert_eq!(errors.len(), 2);
+}
+
+#[test]
+
+fn test_unsupported_syntax_errors() {
+  let input = r#"
+    const foo = [1, 2];
+    export const [a, b] = foo;
+    export const baz = "baz";
+  "#;
+
+  let errors = tests::transform_errors(input, vec!["baz"]);
+
+  assert_eq!(errors.len(), 1);
+  assert!(errors[0].contains("Unsupported syntax"));
+}
+
+#[test]
+
+fn test_transpile_ts() {
+  let input = r#"
+    import type { Theme } from "./theme";
+    const foo: string = "foo";
+    const bar = foo as unknown as Theme;
+    export { bar };
+  "#;
+  let output = r#"
+    const foo = "foo";
+    const bar = foo;
+    export { bar };
+  "#;
+
+  tests::assert_transpiled_fixture("source.ts", input, output, vec!["bar"]);
+}
+
+#[test]
+
+fn test_transpile_ts_skip_shake() {
+  let input = r#"
+    const foo: string = "foo";
+    export const baz = <T,>(value: T): T => value;
+  "#;
+  let output = r#"
+    const foo = "foo";
+    const baz = (value) => value;
+    export { baz };
+  "#;
+
+  tests::assert_transpiled_fixture("source.ts", input, output, vec!["*"]);
+}
+
+#[test]
+
+fn test_source_map() {
+  let input =
+    "const foo = \"foo\";\nconst bar = \"bar\";\nexport function baz() {\n  return bar;\n}\n";
+
+  let map = tests::transform_source_map(input, vec!["baz"]);
+  let lookup_table = map.generate_lookup_table();
+
+  assert_eq!(map.get_source(0), Some("source.js"));
+  assert_eq!(map.get_source_content(0), Some(input));
+
+  // `const bar` is the first line of the output, but the second line of the input
+  let token = map.lookup_token(&lookup_table, 0, 0).unwrap();
+  assert_eq!(token.get_src_line(), 1);
+
+  // `function baz()` is on the second line of the output and on the third line of the input
+  let token = map.lookup_token(&lookup_table, 1, 0).unwrap();
+  assert_eq!(token.get_src_line(), 2);
+}
+
+#[test]
+
+fn test_source_map_with_json_output() {
+  let allocator = Allocator::default();
+  let result = transform_inner(
+    &allocator,
+    &"source.js".to_string(),
+    &"export const foo = \"foo\";\n".to_string(),
+    vec!["foo"],
+    TransformInnerOptions {
+      source_map: true,
+      output_json: true,
+      ..TransformInnerOptions::default()
+    },
+  );
+
+  assert!(result.map.is_some());
+  assert_eq!(
+    result.output.trim(),
+    "const foo = \"foo\";\nexport { foo };"
+  );
+}